@@ -165,6 +165,42 @@ pub trait IteratorExt: Iterator {
     fn some(self) -> bool
     where
         Self: Sized;
+
+    /// Consume the iterator of results partitioning the `Ok` values and the `Err` values into
+    /// separate vectors. Unlike collecting into a `FuResult<Vec<_>>` this doesn't stop on the
+    /// first error.
+    ///
+    /// # Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let (oks, errs) = vec![Ok(1), Err(IterError::item_not_found().into()), Ok(2)].into_iter().split_results();
+    /// assert_eq!(oks, vec![1, 2]);
+    /// assert_eq!(errs.len(), 1);
+    /// ```
+    fn split_results<U>(self) -> (Vec<U>, Vec<FuError>)
+    where
+        Self: Sized,
+        Self: Iterator<Item = FuResult<U>>;
+
+    /// Apply the fallible function `f` to each element of the iterator stopping on and returning
+    /// the first error encountered.
+    ///
+    /// # Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let mut sum = 0;
+    /// assert!(vec![1, 2, 3].into_iter().try_each(|x| -> FuResult<()> {
+    ///     sum += x;
+    ///     Ok(())
+    /// }).is_ok());
+    /// assert_eq!(sum, 6);
+    /// ```
+    fn try_each<F>(self, f: F) -> FuResult<()>
+    where
+        Self: Sized,
+        F: FnMut(Self::Item) -> FuResult<()>;
 }
 
 impl<T: ?Sized> IteratorExt for T
@@ -284,6 +320,33 @@ where
     {
         self.next().is_some()
     }
+
+    fn split_results<U>(self) -> (Vec<U>, Vec<FuError>)
+    where
+        Self: Sized,
+        Self: Iterator<Item = FuResult<U>>,
+    {
+        let mut oks = vec![];
+        let mut errs = vec![];
+        for item in self {
+            match item {
+                Ok(x) => oks.push(x),
+                Err(e) => errs.push(e),
+            }
+        }
+        (oks, errs)
+    }
+
+    fn try_each<F>(mut self, mut f: F) -> FuResult<()>
+    where
+        Self: Sized,
+        F: FnMut(Self::Item) -> FuResult<()>,
+    {
+        for item in &mut self {
+            f(item)?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -393,4 +456,50 @@ mod tests {
         assert_eq!((0..10).filter(|&x| x == 2).some(), true);
         assert_eq!((0..10).filter(|&x| x == 11).some(), false);
     }
+
+    #[test]
+    fn test_split_results() {
+        let items: Vec<FuResult<PathBuf>> = vec![
+            Ok(PathBuf::from("foo")),
+            Err(PathError::Empty.into()),
+            Ok(PathBuf::from("bar")),
+            Err(IterError::ItemNotFound.into()),
+        ];
+        let (oks, errs) = items.into_iter().split_results();
+        assert_eq!(oks, vec![PathBuf::from("foo"), PathBuf::from("bar")]);
+        assert_eq!(errs.len(), 2);
+        assert_eq!(errs[0].downcast_ref::<PathError>(), Some(&PathError::Empty));
+        assert_eq!(errs[1].downcast_ref::<IterError>(), Some(&IterError::ItemNotFound));
+
+        // empty
+        let (oks, errs) = Vec::<FuResult<u32>>::new().into_iter().split_results();
+        assert!(oks.is_empty());
+        assert!(errs.is_empty());
+    }
+
+    #[test]
+    fn test_try_each() {
+        // all succeed
+        let mut ran = vec![];
+        assert!(vec![1, 2, 3].into_iter().try_each(|x| -> FuResult<()> {
+            ran.push(x);
+            Ok(())
+        }).is_ok());
+        assert_iter_eq(&ran, &vec![1, 2, 3]);
+
+        // stops on the third element
+        let mut ran = vec![];
+        let err = vec![1, 2, 3, 4]
+            .into_iter()
+            .try_each(|x| {
+                if x == 3 {
+                    return Err(IterError::item_not_found().into());
+                }
+                ran.push(x);
+                Ok(())
+            })
+            .unwrap_err();
+        assert_eq!(err.downcast_ref::<IterError>(), Some(&IterError::item_not_found()));
+        assert_iter_eq(&ran, &vec![1, 2]);
+    }
 }
@@ -29,3 +29,108 @@ macro_rules! cfgblock {
         $(cfgblock!{#[$attr] $tail})*
     };
 }
+
+/// Create a `HashMap` from a list of key-value pairs with the capacity set to the number of
+/// pairs given.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let map = hashmap!{ "a" => 1, "b" => 2 };
+/// assert_eq!(map["a"], 1);
+/// assert_eq!(map["b"], 2);
+/// assert_eq!(map.get("c"), None);
+/// ```
+#[macro_export]
+macro_rules! hashmap {
+    (@single $($x:tt)*) => (());
+    (@count $($rest:expr),*) => (<[()]>::len(&[$(hashmap!(@single $rest)),*]));
+
+    ($($key:expr => $value:expr,)+) => { hashmap!($($key => $value),+) };
+    ($($key:expr => $value:expr),*) => {
+        {
+            let _cap = hashmap!(@count $($key),*);
+            let mut _map = ::std::collections::HashMap::with_capacity(_cap);
+            $(
+                let _ = _map.insert($key, $value);
+            )*
+            _map
+        }
+    };
+}
+
+/// Create a `HashSet` from a list of elements with the capacity set to the number of elements
+/// given.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let set = hashset!["a", "b"];
+/// assert!(set.contains("a"));
+/// assert!(set.contains("b"));
+/// assert!(!set.contains("c"));
+/// ```
+#[macro_export]
+macro_rules! hashset {
+    (@single $($x:tt)*) => (());
+    (@count $($rest:expr),*) => (<[()]>::len(&[$(hashset!(@single $rest)),*]));
+
+    ($($key:expr,)+) => { hashset!($($key),+) };
+    ($($key:expr),*) => {
+        {
+            let _cap = hashset!(@count $($key),*);
+            let mut _set = ::std::collections::HashSet::with_capacity(_cap);
+            $(
+                let _ = _set.insert($key);
+            )*
+            _set
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    #[test]
+    fn test_hashmap() {
+        // empty
+        let map: HashMap<&str, i32> = hashmap! {};
+        assert!(map.is_empty());
+
+        // entries with and without trailing comma
+        let map = hashmap! { "a" => 1, "b" => 2, "c" => 3 };
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["a"], 1);
+        assert_eq!(map["b"], 2);
+        assert_eq!(map["c"], 3);
+        let map = hashmap! {
+            String::from("a") => vec![1],
+            String::from("b") => vec![2, 3],
+        };
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["b"], vec![2, 3]);
+
+        // later duplicates win
+        let map = hashmap! { "a" => 1, "a" => 2 };
+        assert_eq!(map.len(), 1);
+        assert_eq!(map["a"], 2);
+    }
+
+    #[test]
+    fn test_hashset() {
+        // empty
+        let set: HashSet<i32> = hashset![];
+        assert!(set.is_empty());
+
+        // entries with and without trailing comma
+        let set = hashset![1, 2, 3];
+        assert_eq!(set.len(), 3);
+        assert!(set.contains(&1) && set.contains(&2) && set.contains(&3));
+        let set = hashset!["a", "b", "a",];
+        assert_eq!(set.len(), 2);
+        assert!(set.contains("a") && set.contains("b"));
+    }
+}
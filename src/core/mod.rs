@@ -6,9 +6,11 @@ pub mod macros;
 mod defer;
 mod iter;
 mod option;
+mod result;
 mod string;
 
 pub use defer::*;
 pub use iter::*;
 pub use option::*;
+pub use result::*;
 pub use string::*;
@@ -1,7 +1,11 @@
+use crate::errors::*;
+
 pub trait OptionExt<T> {
     fn has<U>(&self, value: U) -> bool
     where
         U: PartialEq<T>;
+
+    fn to_result<M: Into<String>>(self, msg: M) -> FuResult<T>;
 }
 
 impl<T> OptionExt<T> for Option<T> {
@@ -29,6 +33,23 @@ impl<T> OptionExt<T> for Option<T> {
             None => false,
         }
     }
+
+    /// Converts the option into a [`FuResult`] mapping [`None`] to an [`OptionError`] carrying
+    /// the given context message.
+    ///
+    /// # Examples
+    /// ```
+    /// use fungus::core::*;
+    ///
+    /// assert_eq!(Some(2).to_result("missing").unwrap(), 2);
+    /// assert!(None::<u32>.to_result("missing").is_err());
+    /// ```
+    fn to_result<M: Into<String>>(self, msg: M) -> FuResult<T> {
+        match self {
+            Some(x) => Ok(x),
+            None => Err(OptionError::none_value(msg).into()),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -42,4 +63,13 @@ mod tests {
         assert_eq!(Some(Component::ParentDir).has(Component::ParentDir), true);
         assert_eq!(None.has(Component::ParentDir), false);
     }
+
+    #[test]
+    fn test_to_result() {
+        assert_eq!(Some(2).to_result("foo").unwrap(), 2);
+
+        let err = None::<u32>.to_result("config key missing").unwrap_err();
+        assert!(err.to_string().contains("config key missing"));
+        assert_eq!(err.downcast_ref::<OptionError>(), Some(&OptionError::none_value("config key missing")));
+    }
 }
@@ -0,0 +1,53 @@
+use crate::errors::*;
+
+pub trait ResultExt<T> {
+    fn ok_if_not_found(self) -> FuResult<Option<T>>;
+}
+
+impl<T> ResultExt<T> for FuResult<T> {
+    /// Maps a not found error to `Ok(None)` allowing a missing path to be treated as absence
+    /// rather than failure. Any other error is passed through and `Ok(x)` becomes `Ok(Some(x))`.
+    ///
+    /// # Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!(sys::readstring("tests/temp/foo/bar").ok_if_not_found().unwrap(), None);
+    /// ```
+    fn ok_if_not_found(self) -> FuResult<Option<T>> {
+        match self {
+            Ok(x) => Ok(Some(x)),
+            Err(err) if err.is_not_found() => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_ok_if_not_found() {
+        let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("result_ok_if_not_found");
+        let file1 = tmpdir.mash("file1");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // not found
+        assert_eq!(sys::readstring(&file1).ok_if_not_found().unwrap(), None);
+        let res: FuResult<u32> = Err(PathError::does_not_exist(&file1).into());
+        assert_eq!(res.ok_if_not_found().unwrap(), None);
+
+        // success
+        assert!(sys::write(&file1, "this is a test").is_ok());
+        assert_eq!(sys::readstring(&file1).ok_if_not_found().unwrap(), Some("this is a test".to_string()));
+
+        // other error
+        assert!(sys::readstring(&tmpdir).ok_if_not_found().is_err());
+        let res: FuResult<u32> = Err(PathError::Empty.into());
+        assert_eq!(res.ok_if_not_found().unwrap_err().downcast_ref::<PathError>(), Some(&PathError::Empty));
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+}
@@ -1,4 +1,5 @@
 use crate::errors::*;
+use regex::Regex;
 use std::{ffi::OsStr, path::Path, str};
 
 pub trait StringExt {
@@ -15,6 +16,50 @@ pub trait StringExt {
     /// ```
     fn size(&self) -> usize;
 
+    /// Returns a new [`String`] converted to camelCase. Runs of `-`, `_` or whitespace separators as
+    /// well as existing camelCase boundaries are treated as word breaks.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!("foo-bar_baz".to_camel_case(), "fooBarBaz".to_string());
+    /// ```
+    fn to_camel_case(&self) -> String;
+
+    /// Returns a new [`String`] converted to snake_case. Runs of `-`, `_` or whitespace separators
+    /// as well as existing camelCase boundaries are treated as word breaks.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!("FooBar".to_snake_case(), "foo_bar".to_string());
+    /// ```
+    fn to_snake_case(&self) -> String;
+
+    /// Returns a new [`String`] converted to Title Case. Runs of `-`, `_` or whitespace separators
+    /// as well as existing camelCase boundaries are treated as word breaks.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!("foo-bar_baz".to_title_case(), "Foo Bar Baz".to_string());
+    /// ```
+    fn to_title_case(&self) -> String;
+
+    /// Returns a new [`String`] with the leading match of the given regex trimmed off else the
+    /// original `String`. Only a match starting at the beginning of the string is trimmed.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!("v1.2.3".trim_prefix_rx(&Regex::new(r"v").unwrap()), "1.2.3".to_string());
+    /// ```
+    fn trim_prefix_rx(&self, rx: &Regex) -> String;
+
     /// Returns a new [`String`] with the given `suffix` trimmed off else the original `String`.
     ///
     /// ### Examples
@@ -24,6 +69,17 @@ pub trait StringExt {
     /// assert_eq!("/foo/bar".to_string().trim_suffix("/bar"), "/foo".to_string());
     /// ```
     fn trim_suffix<T: Into<String>>(&self, suffix: T) -> String;
+
+    /// Returns a new [`String`] with the trailing match of the given regex trimmed off else the
+    /// original `String`. Only a match ending at the end of the string is trimmed.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!("1.2.3-rc1".trim_suffix_rx(&Regex::new(r"-rc\d+").unwrap()), "1.2.3".to_string());
+    /// ```
+    fn trim_suffix_rx(&self, rx: &Regex) -> String;
 }
 
 impl StringExt for str {
@@ -31,6 +87,33 @@ impl StringExt for str {
         self.chars().count()
     }
 
+    fn to_camel_case(&self) -> String {
+        words(self)
+            .iter()
+            .enumerate()
+            .map(|(i, x)| match i {
+                0 => x.to_lowercase(),
+                _ => capitalize(x),
+            })
+            .collect()
+    }
+
+    fn to_snake_case(&self) -> String {
+        words(self).iter().map(|x| x.to_lowercase()).collect::<Vec<_>>().join("_")
+    }
+
+    fn to_title_case(&self) -> String {
+        words(self).iter().map(|x| capitalize(x)).collect::<Vec<_>>().join(" ")
+    }
+
+    fn trim_prefix_rx(&self, rx: &Regex) -> String {
+        // The leftmost match is guaranteed to start at 0 if any match does
+        match rx.find(self).filter(|x| x.start() == 0) {
+            Some(x) => self[x.end()..].to_owned(),
+            None => self.to_owned(),
+        }
+    }
+
     fn trim_suffix<T: Into<String>>(&self, suffix: T) -> String {
         let target = suffix.into();
         match self.ends_with(&target) {
@@ -38,6 +121,21 @@ impl StringExt for str {
             _ => self.to_owned(),
         }
     }
+
+    fn trim_suffix_rx(&self, rx: &Regex) -> String {
+        // Retry from each successive match start until a match reaches the end of the string
+        let mut start = 0;
+        while let Some(x) = rx.find_at(self, start) {
+            if x.end() == self.len() {
+                return self[..x.start()].to_owned();
+            }
+            match self[x.start()..].chars().next() {
+                Some(c) => start = x.start() + c.len_utf8(),
+                None => break,
+            }
+        }
+        self.to_owned()
+    }
 }
 
 impl StringExt for String {
@@ -45,6 +143,22 @@ impl StringExt for String {
         self.chars().count()
     }
 
+    fn to_camel_case(&self) -> String {
+        self.as_str().to_camel_case()
+    }
+
+    fn to_snake_case(&self) -> String {
+        self.as_str().to_snake_case()
+    }
+
+    fn to_title_case(&self) -> String {
+        self.as_str().to_title_case()
+    }
+
+    fn trim_prefix_rx(&self, rx: &Regex) -> String {
+        self.as_str().trim_prefix_rx(rx)
+    }
+
     fn trim_suffix<T: Into<String>>(&self, suffix: T) -> String {
         let target = suffix.into();
         match self.ends_with(&target) {
@@ -52,6 +166,49 @@ impl StringExt for String {
             _ => self.to_owned(),
         }
     }
+
+    fn trim_suffix_rx(&self, rx: &Regex) -> String {
+        self.as_str().trim_suffix_rx(rx)
+    }
+}
+
+// Private helper to split the given string into words on runs of `-`, `_` and whitespace as well
+// as on camelCase boundaries e.g. `fooBar` and `HTTPServer`.
+fn words(s: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word = String::new();
+    let chars: Vec<char> = s.chars().collect();
+    for (i, c) in chars.iter().enumerate() {
+        if *c == '-' || *c == '_' || c.is_whitespace() {
+            if !word.is_empty() {
+                words.push(word);
+                word = String::new();
+            }
+            continue;
+        }
+        if c.is_uppercase() && !word.is_empty() {
+            let prev = chars[i - 1];
+            let next_lower = matches!(chars.get(i + 1), Some(x) if x.is_lowercase());
+            if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_lower) {
+                words.push(word);
+                word = String::new();
+            }
+        }
+        word.push(*c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+// Private helper to uppercase the first character of the given word and lowercase the rest.
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(x) => x.to_uppercase().chain(chars.flat_map(|x| x.to_lowercase())).collect(),
+        None => String::new(),
+    }
 }
 
 pub trait ToStringExt {
@@ -104,6 +261,54 @@ mod tests {
         assert_eq!("ƒoo".to_string().size(), 3); // fancy f!
     }
 
+    #[test]
+    fn test_str_to_camel_case() {
+        assert_eq!("foo-bar_baz".to_camel_case(), "fooBarBaz".to_string());
+        assert_eq!("FooBar".to_camel_case(), "fooBar".to_string());
+        assert_eq!("HTTPServer".to_camel_case(), "httpServer".to_string());
+        assert_eq!("__foo--bar  ".to_camel_case(), "fooBar".to_string());
+        assert_eq!("".to_camel_case(), "".to_string());
+    }
+
+    #[test]
+    fn test_string_to_camel_case() {
+        assert_eq!("foo-bar_baz".to_string().to_camel_case(), "fooBarBaz".to_string());
+        assert_eq!("FooBar".to_string().to_camel_case(), "fooBar".to_string());
+        assert_eq!(" -foo bar_".to_string().to_camel_case(), "fooBar".to_string());
+    }
+
+    #[test]
+    fn test_str_to_snake_case() {
+        assert_eq!("foo-bar_baz".to_snake_case(), "foo_bar_baz".to_string());
+        assert_eq!("FooBar".to_snake_case(), "foo_bar".to_string());
+        assert_eq!("fooBar2Baz".to_snake_case(), "foo_bar2_baz".to_string());
+        assert_eq!("HTTPServer".to_snake_case(), "http_server".to_string());
+        assert_eq!("__foo--bar  ".to_snake_case(), "foo_bar".to_string());
+        assert_eq!("".to_snake_case(), "".to_string());
+    }
+
+    #[test]
+    fn test_string_to_snake_case() {
+        assert_eq!("foo-bar_baz".to_string().to_snake_case(), "foo_bar_baz".to_string());
+        assert_eq!("FooBar".to_string().to_snake_case(), "foo_bar".to_string());
+        assert_eq!(" -foo bar_".to_string().to_snake_case(), "foo_bar".to_string());
+    }
+
+    #[test]
+    fn test_str_to_title_case() {
+        assert_eq!("foo-bar_baz".to_title_case(), "Foo Bar Baz".to_string());
+        assert_eq!("FooBar".to_title_case(), "Foo Bar".to_string());
+        assert_eq!("__foo--bar  ".to_title_case(), "Foo Bar".to_string());
+        assert_eq!("".to_title_case(), "".to_string());
+    }
+
+    #[test]
+    fn test_string_to_title_case() {
+        assert_eq!("foo-bar_baz".to_string().to_title_case(), "Foo Bar Baz".to_string());
+        assert_eq!("FooBar".to_string().to_title_case(), "Foo Bar".to_string());
+        assert_eq!(" -foo bar_".to_string().to_title_case(), "Foo Bar".to_string());
+    }
+
     #[test]
     fn test_str_trim_suffix() {
         assert_eq!("foo".trim_suffix("oo"), "f".to_string());
@@ -116,6 +321,45 @@ mod tests {
         assert_eq!("ƒoo".to_string().trim_suffix("o"), "ƒo".to_string()); // fancy f!
     }
 
+    #[test]
+    fn test_str_trim_prefix_rx() {
+        let rx = Regex::new(r"v").unwrap();
+        assert_eq!("v1.2.3".trim_prefix_rx(&rx), "1.2.3".to_string());
+        assert_eq!("1.2.3v".trim_prefix_rx(&rx), "1.2.3v".to_string());
+        assert_eq!("".trim_prefix_rx(&rx), "".to_string());
+        assert_eq!("foo-bar".trim_prefix_rx(&Regex::new(r"\w+-|bar").unwrap()), "bar".to_string());
+    }
+
+    #[test]
+    fn test_string_trim_prefix_rx() {
+        let rx = Regex::new(r"v").unwrap();
+        assert_eq!("v1.2.3".to_string().trim_prefix_rx(&rx), "1.2.3".to_string());
+        assert_eq!("1.2.3".to_string().trim_prefix_rx(&rx), "1.2.3".to_string());
+        assert_eq!("".to_string().trim_prefix_rx(&rx), "".to_string());
+    }
+
+    #[test]
+    fn test_str_trim_suffix_rx() {
+        let rx = Regex::new(r"-rc\d+").unwrap();
+        assert_eq!("1.2.3-rc12".trim_suffix_rx(&rx), "1.2.3".to_string());
+        assert_eq!("1.2.3-rc1-rc2".trim_suffix_rx(&rx), "1.2.3-rc1".to_string());
+        assert_eq!("1-rc1.2.3".trim_suffix_rx(&rx), "1-rc1.2.3".to_string());
+        assert_eq!("".trim_suffix_rx(&rx), "".to_string());
+        assert_eq!("1.2.3-rc1x".trim_suffix_rx(&rx), "1.2.3-rc1x".to_string());
+
+        // builder settings are honored
+        let rx = regex::RegexBuilder::new(r"-rc\d+").case_insensitive(true).build().unwrap();
+        assert_eq!("1.2.3-RC1".trim_suffix_rx(&rx), "1.2.3".to_string());
+    }
+
+    #[test]
+    fn test_string_trim_suffix_rx() {
+        let rx = Regex::new(r"-rc\d+").unwrap();
+        assert_eq!("1.2.3-rc1".to_string().trim_suffix_rx(&rx), "1.2.3".to_string());
+        assert_eq!("1.2.3".to_string().trim_suffix_rx(&rx), "1.2.3".to_string());
+        assert_eq!("".to_string().trim_suffix_rx(&rx), "".to_string());
+    }
+
     #[test]
     fn test_osstr_to_string() {
         assert_eq!(OsStr::new("foo").to_string().unwrap(), "foo".to_string());
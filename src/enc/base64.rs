@@ -0,0 +1,161 @@
+use crate::errors::*;
+
+const STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const URL_SAFE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Alphabet identifies the set of characters used for base64 encoding.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Alphabet {
+    Standard, // RFC 4648 standard alphabet using `+` and `/`
+    UrlSafe,  // RFC 4648 URL and filename safe alphabet using `-` and `_`
+}
+
+impl Alphabet {
+    // Private helper to return the characters of the alphabet
+    fn chars(self) -> &'static [u8; 64] {
+        match self {
+            Alphabet::Standard => STANDARD,
+            Alphabet::UrlSafe => URL_SAFE,
+        }
+    }
+}
+
+/// Encode the given `data` as a padded base64 string using the standard alphabet.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(base64::encode(b"foobar"), "Zm9vYmFy");
+/// ```
+pub fn encode<T: AsRef<[u8]>>(data: T) -> String {
+    encode_p(data, Alphabet::Standard)
+}
+
+/// Encode the given `data` as a padded base64 string using the given `alphabet`.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(base64::encode_p([0xfb, 0xff], base64::Alphabet::UrlSafe), "-_8=");
+/// ```
+pub fn encode_p<T: AsRef<[u8]>>(data: T, alphabet: Alphabet) -> String {
+    let chars = alphabet.chars();
+    let data = data.as_ref();
+    let mut s = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = (chunk[0] as u32) << 16 | (*chunk.get(1).unwrap_or(&0) as u32) << 8 | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => s.push(chars[(n >> (18 - 6 * i) & 0x3f) as usize] as char),
+                false => s.push('='),
+            }
+        }
+    }
+    s
+}
+
+/// Decode the given base64 string `s` using the standard alphabet. Padding is optional but must
+/// be correct when given while any other invalid input returns an error.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(base64::decode("Zm9vYg==").unwrap(), b"foob".to_vec());
+/// assert!(base64::decode("Zm9v!").is_err());
+/// ```
+pub fn decode<T: AsRef<str>>(s: T) -> FuResult<Vec<u8>> {
+    decode_p(s, Alphabet::Standard)
+}
+
+/// Decode the given base64 string `s` using the given `alphabet`. Padding is optional but must be
+/// correct when given while any other invalid input returns an error.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(base64::decode_p("-_8", base64::Alphabet::UrlSafe).unwrap(), vec![0xfb, 0xff]);
+/// ```
+pub fn decode_p<T: AsRef<str>>(s: T, alphabet: Alphabet) -> FuResult<Vec<u8>> {
+    let s = s.as_ref();
+    let chars = alphabet.chars();
+
+    // Strip padding ensuring it completes the final quantum
+    let data = s.trim_end_matches('=').as_bytes();
+    let padding = s.len() - data.len();
+    if data.len() % 4 == 1 || padding > 2 || (padding > 0 && s.len() % 4 != 0) {
+        return Err(EncodingError::invalid_base64(s).into());
+    }
+
+    let mut bytes = Vec::with_capacity(data.len() * 3 / 4);
+    for chunk in data.chunks(4) {
+        let mut n = 0;
+        for (i, c) in chunk.iter().enumerate() {
+            match chars.iter().position(|x| x == c) {
+                Some(x) => n |= (x as u32) << (18 - 6 * i),
+                None => return Err(EncodingError::invalid_base64(s).into()),
+            }
+        }
+        bytes.extend(n.to_be_bytes()[1..chunk.len()].iter());
+    }
+    Ok(bytes)
+}
+
+// Unit tests
+// -------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_decode() {
+        // padded and unpadded
+        assert_eq!(base64::decode("").unwrap(), Vec::<u8>::new());
+        assert_eq!(base64::decode("Zg==").unwrap(), b"f".to_vec());
+        assert_eq!(base64::decode("Zg").unwrap(), b"f".to_vec());
+        assert_eq!(base64::decode("Zm8=").unwrap(), b"fo".to_vec());
+        assert_eq!(base64::decode("Zm8").unwrap(), b"fo".to_vec());
+        assert_eq!(base64::decode("Zm9v").unwrap(), b"foo".to_vec());
+        assert_eq!(base64::decode("Zm9vYmFy").unwrap(), b"foobar".to_vec());
+
+        // alphabets
+        assert_eq!(base64::decode("+/8=").unwrap(), vec![0xfb, 0xff]);
+        assert_eq!(base64::decode_p("-_8=", base64::Alphabet::UrlSafe).unwrap(), vec![0xfb, 0xff]);
+        assert!(base64::decode("-_8=").is_err());
+        assert!(base64::decode_p("+/8=", base64::Alphabet::UrlSafe).is_err());
+
+        // malformed
+        assert_eq!(base64::decode("Z").unwrap_err().downcast_ref::<EncodingError>(), Some(&EncodingError::invalid_base64("Z")));
+        assert!(base64::decode("Zm9vY").is_err());
+        assert!(base64::decode("Zg=").is_err());
+        assert!(base64::decode("Zg===").is_err());
+        assert!(base64::decode("Z===").is_err());
+        assert!(base64::decode("Zm=v").is_err());
+        assert!(base64::decode("Zm9v YmFy").is_err());
+        assert!(base64::decode("Zm9v\n").is_err());
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(base64::encode(b""), "");
+        assert_eq!(base64::encode(b"f"), "Zg==");
+        assert_eq!(base64::encode(b"fo"), "Zm8=");
+        assert_eq!(base64::encode(b"foo"), "Zm9v");
+        assert_eq!(base64::encode(b"foob"), "Zm9vYg==");
+        assert_eq!(base64::encode(b"fooba"), "Zm9vYmE=");
+        assert_eq!(base64::encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64::encode([0xfb, 0xff]), "+/8=");
+        assert_eq!(base64::encode_p([0xfb, 0xff], base64::Alphabet::UrlSafe), "-_8=");
+
+        // round trip
+        let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        for len in 0..10 {
+            assert_eq!(base64::decode(base64::encode(&data[..len])).unwrap(), data[..len].to_vec());
+        }
+        assert_eq!(base64::decode(base64::encode(&data)).unwrap(), data);
+        assert_eq!(base64::decode_p(base64::encode_p(&data, base64::Alphabet::UrlSafe), base64::Alphabet::UrlSafe).unwrap(), data);
+    }
+}
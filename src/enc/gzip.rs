@@ -1,6 +1,39 @@
 use crate::{errors::*, sys::PathExt};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 use std::{fs::File, io::prelude::*, path::Path};
 
+/// Compress the given `data` in memory using gzip with the default compression level.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let data = gzip::compress(b"foo bar").unwrap();
+/// assert_eq!(gzip::decompress(&data).unwrap(), b"foo bar".to_vec());
+/// ```
+pub fn compress(data: &[u8]) -> FuResult<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+/// Decompress the given gzipped `data` in memory. Invalid gzip data returns an error.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let data = gzip::compress(b"foo bar").unwrap();
+/// assert_eq!(gzip::decompress(&data).unwrap(), b"foo bar".to_vec());
+/// assert!(gzip::decompress(b"foo bar").is_err());
+/// ```
+pub fn decompress(data: &[u8]) -> FuResult<Vec<u8>> {
+    let mut decoder = GzDecoder::new(data);
+    let mut buf = Vec::new();
+    decoder.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
 /// Returns true if the given `path` is a gzipped file
 ///
 /// ### Examples
@@ -39,6 +72,23 @@ mod tests {
         temp.mash(path.as_ref())
     }
 
+    #[test]
+    fn test_compress_decompress() {
+        // empty
+        let data = gzip::compress(b"").unwrap();
+        assert_eq!(&data[..2], [0x1f, 0x8b]);
+        assert_eq!(gzip::decompress(&data).unwrap(), Vec::<u8>::new());
+
+        // multi-kilobyte
+        let input: Vec<u8> = (0..10_000).map(|x| (x % 251) as u8).collect();
+        let data = gzip::compress(&input).unwrap();
+        assert_eq!(gzip::decompress(&data).unwrap(), input);
+
+        // garbage
+        assert!(gzip::decompress(b"this is not gzip data").is_err());
+        assert!(gzip::decompress(&data[..data.len() / 2]).is_err());
+    }
+
     #[test]
     fn test_is_gzipped() {
         let tmpdir = setup("is_gzipped");
@@ -0,0 +1,83 @@
+use crate::errors::*;
+
+/// Encode the given `data` as a lowercase hex string.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(hex::encode([0xde, 0xad, 0xbe, 0xef]), "deadbeef");
+/// ```
+pub fn encode<T: AsRef<[u8]>>(data: T) -> String {
+    data.as_ref().iter().map(|x| format!("{:02x}", x)).collect()
+}
+
+/// Decode the given hex string `s` into bytes. Both lowercase and uppercase digits are accepted
+/// while odd length or non hex input returns an error.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(hex::decode("DEADbeef").unwrap(), vec![0xde, 0xad, 0xbe, 0xef]);
+/// assert!(hex::decode("abc").is_err());
+/// ```
+pub fn decode<T: AsRef<str>>(s: T) -> FuResult<Vec<u8>> {
+    let s = s.as_ref();
+    if s.len() % 2 != 0 {
+        return Err(EncodingError::invalid_hex(s).into());
+    }
+    let mut data = Vec::with_capacity(s.len() / 2);
+    for pair in s.as_bytes().chunks(2) {
+        match (digit(pair[0]), digit(pair[1])) {
+            (Some(x), Some(y)) => data.push(x << 4 | y),
+            _ => return Err(EncodingError::invalid_hex(s).into()),
+        }
+    }
+    Ok(data)
+}
+
+// Private helper to convert the given hex digit into its value
+fn digit(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+// Unit tests
+// -------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_decode() {
+        // lowercase and uppercase
+        assert_eq!(hex::decode("00ff7f10").unwrap(), vec![0x00, 0xff, 0x7f, 0x10]);
+        assert_eq!(hex::decode("00FF7F10").unwrap(), vec![0x00, 0xff, 0x7f, 0x10]);
+
+        // empty
+        assert_eq!(hex::decode("").unwrap(), Vec::<u8>::new());
+
+        // malformed
+        assert_eq!(hex::decode("abc").unwrap_err().downcast_ref::<EncodingError>(), Some(&EncodingError::invalid_hex("abc")));
+        assert!(hex::decode("zz").is_err());
+        assert!(hex::decode("0x10").is_err());
+        assert!(hex::decode("é0").is_err());
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(hex::encode([]), "");
+        assert_eq!(hex::encode([0x00, 0x0f, 0xff]), "000fff");
+        assert_eq!(hex::encode(b"foo"), "666f6f");
+
+        // round trip
+        let data: Vec<u8> = (0..=255).collect();
+        assert_eq!(hex::decode(hex::encode(&data)).unwrap(), data);
+        assert_eq!(hex::decode(hex::encode(sys::digest("Cargo.toml").unwrap())).unwrap(), sys::digest("Cargo.toml").unwrap());
+    }
+}
@@ -1,2 +1,4 @@
+pub mod base64;
 pub mod gzip;
+pub mod hex;
 pub mod tar;
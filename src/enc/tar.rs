@@ -3,11 +3,68 @@ use crate::{
     errors::*,
     sys::{self, PathExt},
 };
-use flate2::{self, read::GzDecoder, write::GzEncoder, Compression};
-use std::{fs::File, path::Path};
+use flate2::{self, read::GzDecoder, write::GzEncoder};
+use std::{
+    fs::{File, OpenOptions},
+    io::{prelude::*, SeekFrom},
+    path::{Path, PathBuf},
+};
+use walkdir::WalkDir;
 
-/// Create a tarball `tarfile` uing gzip compression from the files implicated by the `glob`.
-/// Handles file globbing and recursively adds source files based on glob.
+/// Append the given `file` to the existing uncompressed tarball `tarfile` under the given
+/// `arcname` defaulting to the file's base name. Only uncompressed archives can be appended to,
+/// gzip, zstd, xz or bzip2 compressed archives return an error and must be recreated instead.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("tar_append_doc");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// let tarball = tmpdir.mash("tarball.tar");
+/// assert!(sys::copyfile("tests/alpine-base.tar", &tarball).is_ok());
+/// assert!(sys::write(&file1, "single file\n").is_ok());
+/// assert!(tar::append(&tarball, &file1, Some("foo/file1")).is_ok());
+/// assert!(tar::list(&tarball).unwrap().contains(&PathBuf::from("foo/file1")));
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn append<T: AsRef<Path>, U: AsRef<Path>>(tarfile: T, file: U, arcname: Option<&str>) -> FuResult<()> {
+    let tarfile = tarfile.as_ref().abs()?;
+    let file = file.as_ref().abs()?;
+    if is_compressed(&tarfile)? {
+        return Err(FileError::not_appendable(&tarfile).into());
+    }
+
+    // Locate the end of the last entry so the trailing zero blocks get overwritten
+    let mut end = 0;
+    let mut archive = tar::Archive::new(File::open(&tarfile)?);
+    for entry in archive.entries()? {
+        let entry = entry?;
+        end = entry.raw_file_position() + entry.size().div_ceil(512) * 512;
+    }
+
+    // Append the new entry and write out a new end of archive marker
+    let mut f = OpenOptions::new().write(true).open(&tarfile)?;
+    f.set_len(end)?;
+    f.seek(SeekFrom::Start(end))?;
+    let name = match arcname {
+        Some(x) => x.to_string(),
+        None => file.base()?,
+    };
+    let mut tarball = tar::Builder::new(f);
+    tarball.append_path_with_name(&file, name)?;
+    tarball.finish()?;
+
+    Ok(())
+}
+
+/// Create a tarball `dst` from the files implicated by the `src` glob returning the absolute path
+/// of the tarball. Sources are walked recursively in sorted order and added with paths relative
+/// to their parent directory. Unix modes are preserved and links are stored as links rather than
+/// followed. The tarball is always gzip compressed using flate2's default level, use
+/// `create_plain` for an uncompressed tarball.
 ///
 /// ### Examples
 /// ```
@@ -21,39 +78,122 @@ use std::{fs::File, path::Path};
 /// let dst = tmpdir.mash("dst");
 /// let dstfile = dst.mash("file1");
 /// assert!(sys::write(&file1, "single file\n").is_ok());
-/// assert!(tar::create(&tarball, &file1).is_ok());
+/// assert_eq!(tar::create(&tarball, &file1).unwrap(), tarball);
 /// assert!(tar::extract_all(&tarball, &dst).is_ok());
 /// assert_eq!(sys::readstring(&dstfile).unwrap(), "single file\n".to_string());
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// ```
-pub fn create<T: AsRef<Path>, U: AsRef<Path>>(tarfile: T, glob: U) -> FuResult<()> {
-    let tarfile = tarfile.as_ref().abs()?;
+pub fn create<T: AsRef<Path>, U: AsRef<Path>>(dst: T, src: U) -> FuResult<PathBuf> {
+    let dst = dst.as_ref().abs()?;
+    let sources = sources(src)?;
+    let encoder = GzEncoder::new(File::create(&dst)?, flate2::Compression::default());
+    build(tar::Builder::new(encoder), &sources)?.finish()?;
+    Ok(dst)
+}
 
-    // Handle globbing
-    let sources = sys::glob(glob.as_ref())?;
-    if sources.is_empty() {
-        return Err(PathError::does_not_exist(glob.as_ref()).into());
+/// Create a gzip compressed tarball `dst` from the files implicated by the `src` glob returning
+/// the absolute path of the tarball. Behaves identically to `create` but requires `dst` to have a
+/// `.tgz` or `.tar.gz` suffix and allows for choosing the compression `level`. The compression
+/// `level` ranges from 0-9 and defaults to flate2's default level when `None`.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("tar_create_gz_doc");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// let tarball = tmpdir.mash("tarball.tar.gz");
+/// let dst = tmpdir.mash("dst");
+/// assert!(sys::write(&file1, "single file\n").is_ok());
+/// assert_eq!(tar::create_gz(&tarball, &file1, Some(9)).unwrap(), tarball);
+/// assert!(tar::extract_all(&tarball, &dst).is_ok());
+/// assert_eq!(sys::readstring(dst.mash("file1")).unwrap(), "single file\n".to_string());
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn create_gz<T: AsRef<Path>, U: AsRef<Path>>(dst: T, src: U, level: Option<u32>) -> FuResult<PathBuf> {
+    let dst = dst.as_ref().abs()?;
+    if !is_gz_name(&dst)? {
+        return Err(FileError::invalid_gzip_suffix(&dst).into());
     }
+    let sources = sources(src)?;
+    let level = match level {
+        Some(x) => flate2::Compression::new(x),
+        None => flate2::Compression::default(),
+    };
+    let encoder = GzEncoder::new(File::create(&dst)?, level);
+    build(tar::Builder::new(encoder), &sources)?.finish()?;
+    Ok(dst)
+}
 
-    // Create the tarfile
-    let tar_gz = File::create(&tarfile)?;
-    let encoder = GzEncoder::new(tar_gz, Compression::default());
-    let mut tarball = tar::Builder::new(encoder);
+/// Create an uncompressed tarball `dst` from the files implicated by the `src` glob returning the
+/// absolute path of the tarball. Behaves identically to `create` but skips the gzip compression
+/// so the tarball can later be added to with `append`.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("tar_create_plain_doc");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// let tarball = tmpdir.mash("tarball.tar");
+/// let dst = tmpdir.mash("dst");
+/// assert!(sys::write(&file1, "single file\n").is_ok());
+/// assert_eq!(tar::create_plain(&tarball, &file1).unwrap(), tarball);
+/// assert!(!gzip::is_gzipped(&tarball).unwrap());
+/// assert!(tar::extract_all(&tarball, &dst).is_ok());
+/// assert_eq!(sys::readstring(dst.mash("file1")).unwrap(), "single file\n".to_string());
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn create_plain<T: AsRef<Path>, U: AsRef<Path>>(dst: T, src: U) -> FuResult<PathBuf> {
+    let dst = dst.as_ref().abs()?;
+    let sources = sources(src)?;
+    build(tar::Builder::new(File::create(&dst)?), &sources)?;
+    Ok(dst)
+}
 
-    // Include all source files in the tarball
+// Private helper to add the given sources to the tarball, finishing and returning the writer
+fn build<W: Write>(mut tarball: tar::Builder<W>, sources: &[PathBuf]) -> FuResult<W> {
+    tarball.follow_symlinks(false);
     for source in sources {
-        if source.is_file() {
-            let mut f = File::open(&source)?;
-            tarball.append_file(source.base()?, &mut f)?;
-        } else {
-            tarball.append_dir_all(source.base()?, &source)?;
+        let root = source.dir()?;
+        for entry in WalkDir::new(source).follow_links(false).sort_by(|x, y| x.file_name().cmp(y.file_name())) {
+            let entry = entry?;
+            let name = entry.path().strip_prefix(&root).map_err(|_| PathError::failed_to_string(entry.path()))?;
+            tarball.append_path_with_name(entry.path(), name)?;
         }
     }
+    Ok(tarball.into_inner()?)
+}
 
-    Ok(())
+// Private helper to check if the given tarball path has a gzip suffix
+fn is_gz_name<T: AsRef<Path>>(path: T) -> FuResult<bool> {
+    let name = path.as_ref().base()?;
+    Ok(name.ends_with(".tgz") || name.ends_with(".tar.gz"))
+}
+
+// Private helper to expand the given `src` glob into the sources to archive
+fn sources<T: AsRef<Path>>(src: T) -> FuResult<Vec<PathBuf>> {
+    let sources = sys::glob(src.as_ref())?;
+    if sources.is_empty() {
+        return Err(PathError::does_not_exist(src.as_ref()).into());
+    }
+    Ok(sources)
 }
 
-/// Extract all tarball files into the given `dst` directory.
+/// Compression provides a hint for how to read a tarball during extraction.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Compression {
+    Auto, // detect gzip compression from the tarball's header
+    Gzip, // always read the tarball as gzip compressed
+    None, // always read the tarball as uncompressed
+}
+
+/// Extract all tarball files into the given `dst` directory. Gzip compression is detected from
+/// the tarball's header so both `.tar` and `.tgz` tarballs work regardless of extension.
 ///
 /// ### Examples
 /// ```
@@ -73,23 +213,80 @@ pub fn create<T: AsRef<Path>, U: AsRef<Path>>(tarfile: T, glob: U) -> FuResult<(
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// ```
 pub fn extract_all<T: AsRef<Path>, U: AsRef<Path>>(tarfile: T, dst: U) -> FuResult<()> {
+    extract_all_to(tarfile, dst, Compression::Auto)
+}
+
+/// Extract all tarball files into the given `dst` directory reading the tarball according to the
+/// given `compression` hint rather than always detecting it.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("tar_extract_all_to_doc");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(tar::extract_all_to("tests/alpine-base.tar", &tmpdir, tar::Compression::None).is_ok());
+/// assert!(tmpdir.mash("README.md").exists());
+/// assert!(tar::extract_all_to("tests/alpine-base.tar", &tmpdir, tar::Compression::Gzip).is_err());
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn extract_all_to<T: AsRef<Path>, U: AsRef<Path>>(tarfile: T, dst: U, compression: Compression) -> FuResult<()> {
     let dst = dst.as_ref().abs()?;
     let tarfile = tarfile.as_ref().abs()?;
+    let gzipped = match compression {
+        Compression::Auto => gzip::is_gzipped(&tarfile)?,
+        Compression::Gzip => true,
+        Compression::None => false,
+    };
 
-    if gzip::is_gzipped(&tarfile)? {
-        let f = File::open(&tarfile)?;
-        let tar = GzDecoder::new(f);
-        let mut archive = tar::Archive::new(tar);
-        archive.unpack(&dst)?;
+    let f = File::open(&tarfile)?;
+    if gzipped {
+        tar::Archive::new(GzDecoder::new(f)).unpack(&dst)?;
     } else {
-        let f = File::open(&tarfile)?;
-        let mut archive = tar::Archive::new(f);
-        archive.unpack(&dst)?;
+        tar::Archive::new(f).unpack(&dst)?;
     }
 
     Ok(())
 }
 
+/// List the paths of all entries in the given tarball. Handles gzipped tarballs.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tarball = PathBuf::from("tests/alpine-base.tgz");
+/// assert!(tar::list(&tarball).unwrap().contains(&PathBuf::from("README.md")));
+/// ```
+pub fn list<T: AsRef<Path>>(tarfile: T) -> FuResult<Vec<PathBuf>> {
+    let tarfile = tarfile.as_ref().abs()?;
+    let mut paths = vec![];
+
+    if gzip::is_gzipped(&tarfile)? {
+        let mut archive = tar::Archive::new(GzDecoder::new(File::open(&tarfile)?));
+        for entry in archive.entries()? {
+            paths.push(entry?.path()?.to_path_buf());
+        }
+    } else {
+        let mut archive = tar::Archive::new(File::open(&tarfile)?);
+        for entry in archive.entries()? {
+            paths.push(entry?.path()?.to_path_buf());
+        }
+    }
+
+    Ok(paths)
+}
+
+// Private helper to detect gzip, zstd, xz or bzip2 compressed files by their magic bytes
+fn is_compressed(path: &Path) -> FuResult<bool> {
+    let mut buffer = vec![];
+    File::open(path)?.take(6).read_to_end(&mut buffer)?;
+    Ok(buffer.starts_with(&[0x1f, 0x8b])
+        || buffer.starts_with(&[0x28, 0xb5, 0x2f, 0xfd])
+        || buffer.starts_with(&[0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00])
+        || buffer.starts_with(b"BZh"))
+}
+
 // Unit tests
 // -------------------------------------------------------------------------------------------------
 #[cfg(test)]
@@ -103,6 +300,43 @@ mod tests {
         temp.mash(path.as_ref())
     }
 
+    #[test]
+    fn test_append_and_list() {
+        let tmpdir = setup("tar_append_and_list");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("file2");
+        let tarball = tmpdir.mash("tarball.tar");
+        let tgz = tmpdir.mash("tarball.tgz");
+        let dst = tmpdir.mash("dst");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // Append to an existing uncompressed tarball
+        assert!(sys::copyfile(tmpdir.mash("../../alpine-base.tar"), &tarball).is_ok());
+        let count = tar::list(&tarball).unwrap().len();
+        assert!(sys::write(&file1, "single file1\n").is_ok());
+        assert!(sys::write(&file2, "single file2\n").is_ok());
+        assert!(tar::append(&tarball, &file1, None).is_ok());
+        assert!(tar::append(&tarball, &file2, Some("dir1/file2")).is_ok());
+        let paths = tar::list(&tarball).unwrap();
+        assert_eq!(paths.len(), count + 2);
+        assert_eq!(paths[count..], [PathBuf::from("file1"), PathBuf::from("dir1/file2")]);
+
+        // Extract to verify the archive is still intact
+        assert!(tar::extract_all(&tarball, &dst).is_ok());
+        assert_eq!(sys::readlines(dst.mash("README.md")).unwrap()[0], "alpine-base".to_string());
+        assert_eq!(sys::readstring(dst.mash("file1")).unwrap(), "single file1\n".to_string());
+        assert_eq!(sys::readstring(dst.mash("dir1/file2")).unwrap(), "single file2\n".to_string());
+
+        // Compressed tarballs can't be appended to
+        assert!(tar::create(&tgz, &file1).is_ok());
+        assert_eq!(tar::list(&tgz).unwrap(), vec![PathBuf::from("file1")]);
+        let err = tar::append(&tgz, &file2, None).unwrap_err();
+        assert_eq!(err.downcast_ref::<FileError>(), Some(&FileError::not_appendable(&tgz)));
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_create_and_extract_multiple() {
         let tmpdir = setup("tar_create_and_extract_multile");
@@ -180,6 +414,119 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_create_gz() {
+        let tmpdir = setup("tar_create_gz");
+        let dir1 = tmpdir.mash("dir1");
+        let tgz = tmpdir.mash("tarball.tgz");
+        let fast = tmpdir.mash("fast.tar.gz");
+        let dst1 = tmpdir.mash("dst1");
+        let dst2 = tmpdir.mash("dst2");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(dir1.mash("dir2")).is_ok());
+        assert!(sys::write(dir1.mash("file1"), "file1\n".repeat(100)).is_ok());
+        assert!(sys::write(dir1.mash("dir2/file2"), "file2\n").is_ok());
+        assert!(sys::symlink(dir1.mash("link1"), "file1").is_ok());
+
+        // Suffix is required
+        let err = tar::create_gz(tmpdir.mash("tarball.tar"), &dir1, None).unwrap_err();
+        assert_eq!(err.downcast_ref::<FileError>(), Some(&FileError::invalid_gzip_suffix(tmpdir.mash("tarball.tar"))));
+
+        // Round trip with default and explicit levels
+        assert_eq!(tar::create_gz(&tgz, &dir1, None).unwrap(), tgz);
+        assert_eq!(tar::create_gz(&fast, &dir1, Some(1)).unwrap(), fast);
+        assert!(gzip::is_gzipped(&tgz).unwrap());
+        assert!(gzip::is_gzipped(&fast).unwrap());
+        assert!(tar::extract_all(&tgz, &dst1).is_ok());
+        assert!(tar::extract_all(&fast, &dst2).is_ok());
+        let expected: Vec<PathBuf> = sys::all_paths(&dir1).unwrap().iter().map(|x| x.trim_prefix(&tmpdir)).collect();
+        for dst in [&dst1, &dst2].iter() {
+            assert_iter_eq(sys::all_paths(dst.mash("dir1")).unwrap().iter().map(|x| x.trim_prefix(dst)).collect::<Vec<PathBuf>>(), expected.clone());
+        }
+        assert_eq!(sys::readstring(dst2.mash("dir1/file1")).unwrap(), "file1\n".repeat(100));
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_create_tree() {
+        let tmpdir = setup("tar_create_tree");
+        let dir1 = tmpdir.mash("dir1");
+        let tarball = tmpdir.mash("tarball.tar");
+        let tgz = tmpdir.mash("tarball.tar.gz");
+        let dst1 = tmpdir.mash("dst1");
+        let dst2 = tmpdir.mash("dst2");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+
+        // Create a small tree with a link
+        assert!(sys::mkdir(dir1.mash("dir2")).is_ok());
+        assert!(sys::write_p(dir1.mash("file1"), "file1\n", 0o755).is_ok());
+        assert!(sys::write_p(dir1.mash("dir2/file2"), "file2\n", 0o600).is_ok());
+        assert!(sys::symlink(dir1.mash("link1"), "file1").is_ok());
+
+        // Uncompressed round trip
+        assert_eq!(tar::create_plain(&tarball, &dir1).unwrap(), tarball);
+        assert!(!gzip::is_gzipped(&tarball).unwrap());
+        let paths = vec![PathBuf::from("dir1"), PathBuf::from("dir1/dir2"), PathBuf::from("dir1/dir2/file2"), PathBuf::from("dir1/file1"), PathBuf::from("dir1/link1")];
+        assert_eq!(tar::list(&tarball).unwrap(), paths);
+        assert!(tar::extract_all(&tarball, &dst1).is_ok());
+        let expected: Vec<PathBuf> = sys::all_paths(&dir1).unwrap().iter().map(|x| dst1.mash(x.trim_prefix(&tmpdir))).collect();
+        assert_iter_eq(sys::all_paths(dst1.mash("dir1")).unwrap(), expected);
+        assert_eq!(dst1.mash("dir1/file1").mode().unwrap(), 0o100755);
+        assert_eq!(dst1.mash("dir1/dir2/file2").mode().unwrap(), 0o100600);
+        assert_eq!(sys::readlink(dst1.mash("dir1/link1")).unwrap(), PathBuf::from("file1"));
+
+        // Compressed regardless of name
+        assert!(tar::create(&tgz, &dir1).is_ok());
+        assert!(gzip::is_gzipped(&tgz).unwrap());
+        assert!(tar::create(&tarball, &dir1).is_ok());
+        assert!(gzip::is_gzipped(&tarball).unwrap());
+        assert!(tar::extract_all(&tgz, &dst2).is_ok());
+        assert_eq!(sys::readstring(dst2.mash("dir1/dir2/file2")).unwrap(), "file2\n".to_string());
+        assert!(dst2.mash("dir1/link1").is_symlink());
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_extract_all_to() {
+        let tmpdir = setup("tar_extract_all_to");
+        let dir1 = tmpdir.mash("dir1");
+        let tarball = tmpdir.mash("tarball.dat");
+        let tgz = tmpdir.mash("tgz.dat");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(dir1.mash("dir2")).is_ok());
+        assert!(sys::write(dir1.mash("file1"), "file1\n").is_ok());
+        assert!(sys::write(dir1.mash("dir2/file2"), "file2\n").is_ok());
+
+        // Create plain and gzipped tarballs of the same tree with misleading extensions
+        assert!(tar::create_plain(tmpdir.mash("tarball.tar"), &dir1).is_ok());
+        assert!(tar::create(tmpdir.mash("tarball.tgz"), &dir1).is_ok());
+        assert!(sys::move_p(tmpdir.mash("tarball.tar"), &tarball).is_ok());
+        assert!(sys::move_p(tmpdir.mash("tarball.tgz"), &tgz).is_ok());
+
+        // Detection and explicit hints produce identical results
+        let expected: Vec<PathBuf> = sys::all_paths(&dir1).unwrap().iter().map(|x| x.trim_prefix(&tmpdir)).collect();
+        let cases = vec![
+            (&tarball, tar::Compression::Auto, "dst1"),
+            (&tgz, tar::Compression::Auto, "dst2"),
+            (&tarball, tar::Compression::None, "dst3"),
+            (&tgz, tar::Compression::Gzip, "dst4"),
+        ];
+        for (src, compression, dst) in cases {
+            let dst = tmpdir.mash(dst);
+            assert!(tar::extract_all_to(src, &dst, compression).is_ok());
+            assert_iter_eq(sys::all_paths(dst.mash("dir1")).unwrap().iter().map(|x| x.trim_prefix(&dst)).collect::<Vec<PathBuf>>(), expected.clone());
+            assert_eq!(sys::readstring(dst.mash("dir1/dir2/file2")).unwrap(), "file2\n");
+        }
+
+        // Forcing the wrong hint fails
+        assert!(tar::extract_all_to(&tarball, tmpdir.mash("dst5"), tar::Compression::Gzip).is_err());
+        assert!(tar::extract_all_to(&tgz, tmpdir.mash("dst6"), tar::Compression::None).is_err());
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_extract_sys_tgz() {
         let tmpdir = setup("tar_extract_sys_tgz");
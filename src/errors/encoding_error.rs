@@ -0,0 +1,46 @@
+use std::{error::Error as StdError, fmt};
+
+// An error indicating that something went wrong with an encoding operation
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum EncodingError {
+    /// An error indicating that the given base64 string is invalid.
+    InvalidBase64(String),
+
+    /// An error indicating that the given hex string is invalid.
+    InvalidHex(String),
+}
+impl EncodingError {
+    /// Return an error indicating that the given base64 string is invalid
+    pub fn invalid_base64<T: AsRef<str>>(value: T) -> EncodingError {
+        EncodingError::InvalidBase64(value.as_ref().to_string())
+    }
+
+    /// Return an error indicating that the given hex string is invalid
+    pub fn invalid_hex<T: AsRef<str>>(value: T) -> EncodingError {
+        EncodingError::InvalidHex(value.as_ref().to_string())
+    }
+}
+
+impl StdError for EncodingError {}
+
+impl fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EncodingError::InvalidBase64(ref value) => write!(f, "invalid base64 string: {}", value),
+            EncodingError::InvalidHex(ref value) => write!(f, "invalid hex string: {}", value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::*;
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(EncodingError::invalid_base64("foo"), EncodingError::InvalidBase64("foo".to_string()));
+        assert_eq!(format!("{}", EncodingError::invalid_base64("foo")), "invalid base64 string: foo");
+        assert_eq!(EncodingError::invalid_hex("foo"), EncodingError::InvalidHex("foo".to_string()));
+        assert_eq!(format!("{}", EncodingError::invalid_hex("foo")), "invalid hex string: foo");
+    }
+}
@@ -7,11 +7,13 @@ pub type FuResult<T> = std::result::Result<T, FuError>;
 /// Define common error wrapper type
 #[derive(Debug)]
 pub enum FuError {
+    Encoding(EncodingError),
     File(FileError),
     GlobPattern(glob::PatternError),
     Io(io::Error),
     Iter(IterError),
     Nul(ffi::NulError),
+    Option(OptionError),
     Os(OsError),
     Path(PathError),
     Regex(regex::Error),
@@ -36,6 +38,19 @@ impl FuError {
         self.as_mut().downcast_mut::<T>()
     }
 
+    /// Returns true if the error indicates that a path or file was not found
+    pub fn is_not_found(&self) -> bool {
+        match *self {
+            FuError::Io(ref err) => err.kind() == io::ErrorKind::NotFound,
+            FuError::Path(PathError::DoesNotExist(_)) => true,
+            FuError::WalkDir(ref err) => match err.io_error() {
+                Some(x) => x.kind() == io::ErrorKind::NotFound,
+                None => false,
+            },
+            _ => false,
+        }
+    }
+
     /// Implemented directly on the `Error` type to reduce casting required
     pub fn source(&self) -> Option<&(dyn StdError+'static)> {
         self.as_ref().source()
@@ -45,11 +60,13 @@ impl FuError {
 impl std::fmt::Display for FuError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
+            FuError::Encoding(ref err) => write!(f, "{}", err),
             FuError::File(ref err) => write!(f, "{}", err),
             FuError::GlobPattern(ref err) => write!(f, "{}", err),
             FuError::Io(ref err) => write!(f, "{}", err),
             FuError::Iter(ref err) => write!(f, "{}", err),
             FuError::Nul(ref err) => write!(f, "{}", err),
+            FuError::Option(ref err) => write!(f, "{}", err),
             FuError::Os(ref err) => write!(f, "{}", err),
             FuError::Path(ref err) => write!(f, "{}", err),
             FuError::Regex(ref err) => write!(f, "{}", err),
@@ -64,11 +81,13 @@ impl std::fmt::Display for FuError {
 impl AsRef<dyn StdError> for FuError {
     fn as_ref(&self) -> &(dyn StdError+'static) {
         match *self {
+            FuError::Encoding(ref err) => err,
             FuError::File(ref err) => err,
             FuError::GlobPattern(ref err) => err,
             FuError::Io(ref err) => err,
             FuError::Iter(ref err) => err,
             FuError::Nul(ref err) => err,
+            FuError::Option(ref err) => err,
             FuError::Os(ref err) => err,
             FuError::Path(ref err) => err,
             FuError::Regex(ref err) => err,
@@ -83,11 +102,13 @@ impl AsRef<dyn StdError> for FuError {
 impl AsMut<dyn StdError> for FuError {
     fn as_mut(&mut self) -> &mut (dyn StdError+'static) {
         match *self {
+            FuError::Encoding(ref mut err) => err,
             FuError::File(ref mut err) => err,
             FuError::GlobPattern(ref mut err) => err,
             FuError::Io(ref mut err) => err,
             FuError::Iter(ref mut err) => err,
             FuError::Nul(ref mut err) => err,
+            FuError::Option(ref mut err) => err,
             FuError::Os(ref mut err) => err,
             FuError::Path(ref mut err) => err,
             FuError::Regex(ref mut err) => err,
@@ -99,6 +120,12 @@ impl AsMut<dyn StdError> for FuError {
     }
 }
 
+impl From<EncodingError> for FuError {
+    fn from(err: EncodingError) -> FuError {
+        FuError::Encoding(err)
+    }
+}
+
 impl From<FileError> for FuError {
     fn from(err: FileError) -> FuError {
         FuError::File(err)
@@ -129,6 +156,12 @@ impl From<ffi::NulError> for FuError {
     }
 }
 
+impl From<OptionError> for FuError {
+    fn from(err: OptionError) -> FuError {
+        FuError::Option(err)
+    }
+}
+
 impl From<OsError> for FuError {
     fn from(err: OsError) -> FuError {
         FuError::Os(err)
@@ -182,6 +215,14 @@ mod tests {
 
     #[test]
     fn test_error() {
+        let mut err = FuError::from(EncodingError::invalid_hex("foo"));
+        assert_eq!("invalid hex string: foo", err.to_string());
+        assert_eq!("invalid hex string: foo", err.as_ref().to_string());
+        assert_eq!("invalid hex string: foo", err.as_mut().to_string());
+        assert!(err.downcast_ref::<EncodingError>().is_some());
+        assert!(err.downcast_mut::<EncodingError>().is_some());
+        assert!(err.source().is_none());
+
         let mut err = FuError::from(FileError::FailedToExtractString);
         assert_eq!("failed to extract string from file", err.to_string());
         assert_eq!("failed to extract string from file", err.as_ref().to_string());
@@ -222,6 +263,14 @@ mod tests {
         assert!(err.downcast_mut::<std::ffi::NulError>().is_some());
         assert!(err.source().is_none());
 
+        let mut err = FuError::from(OptionError::none_value("foo"));
+        assert_eq!("option was none: foo", err.to_string());
+        assert_eq!("option was none: foo", err.as_ref().to_string());
+        assert_eq!("option was none: foo", err.as_mut().to_string());
+        assert!(err.downcast_ref::<OptionError>().is_some());
+        assert!(err.downcast_mut::<OptionError>().is_some());
+        assert!(err.source().is_none());
+
         let mut err = FuError::from(OsError::KernelReleaseNotFound);
         assert_eq!("kernel release was not found", err.to_string());
         assert_eq!("kernel release was not found", err.as_ref().to_string());
@@ -271,6 +320,15 @@ mod tests {
         assert!(err.source().is_none());
     }
 
+    #[test]
+    fn test_is_not_found() {
+        assert!(FuError::from(io::Error::new(io::ErrorKind::NotFound, "foo")).is_not_found());
+        assert!(FuError::from(PathError::does_not_exist("foo")).is_not_found());
+        assert!(sys::readstring("tests/temp/error_is_not_found").unwrap_err().is_not_found());
+        assert!(!FuError::from(io::Error::new(io::ErrorKind::AlreadyExists, "foo")).is_not_found());
+        assert!(!FuError::from(PathError::Empty).is_not_found());
+    }
+
     #[test]
     fn test_is() {
         assert!(path_empty().is_err());
@@ -1,10 +1,47 @@
-use std::{error::Error as StdError, fmt};
+use std::{
+    error::Error as StdError,
+    fmt,
+    path::{Path, PathBuf},
+};
 
 // An error indicating that something went wrong with a file operation
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum FileError {
+    /// An error indicating that the mode of the given paths could not be changed.
+    FailedToChmod(Vec<PathBuf>),
+
     /// An error indicating that a regex string extraction failed.
     FailedToExtractString,
+
+    /// An error indicating that the given gzip tarball is missing a `.tgz` or `.tar.gz` suffix.
+    InvalidGzipSuffix(PathBuf),
+
+    /// An error indicating that the given regex pattern failed to compile.
+    InvalidPattern { pattern: String, source: String },
+
+    /// An error indicating that the given archive is compressed and can't be appended to.
+    NotAppendable(PathBuf),
+}
+impl FileError {
+    /// Return an error indicating that the mode of the given paths could not be changed
+    pub fn failed_to_chmod<T: AsRef<Path>>(paths: Vec<T>) -> FileError {
+        FileError::FailedToChmod(paths.iter().map(|x| x.as_ref().to_path_buf()).collect())
+    }
+
+    /// Return an error indicating that the given gzip tarball is missing a `.tgz` or `.tar.gz` suffix
+    pub fn invalid_gzip_suffix<T: AsRef<Path>>(path: T) -> FileError {
+        FileError::InvalidGzipSuffix(path.as_ref().to_path_buf())
+    }
+
+    /// Return an error indicating that the given regex pattern failed to compile
+    pub fn invalid_pattern<T: AsRef<str>>(pattern: T, source: regex::Error) -> FileError {
+        FileError::InvalidPattern { pattern: pattern.as_ref().to_string(), source: source.to_string() }
+    }
+
+    /// Return an error indicating that the given archive is compressed and can't be appended to
+    pub fn not_appendable<T: AsRef<Path>>(path: T) -> FileError {
+        FileError::NotAppendable(path.as_ref().to_path_buf())
+    }
 }
 
 impl StdError for FileError {}
@@ -12,15 +49,29 @@ impl StdError for FileError {}
 impl fmt::Display for FileError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
+            FileError::FailedToChmod(ref paths) => write!(f, "failed to change mode for paths: {}", paths.iter().map(|x| x.display().to_string()).collect::<Vec<String>>().join(", ")),
             FileError::FailedToExtractString => write!(f, "failed to extract string from file"),
+            FileError::InvalidGzipSuffix(ref path) => write!(f, "gzip tarball must have a .tgz or .tar.gz suffix: {}", path.display()),
+            FileError::InvalidPattern { ref pattern, ref source } => write!(f, "invalid regex pattern '{}': {}", pattern, source),
+            FileError::NotAppendable(ref path) => write!(f, "archive is compressed and can't be appended to: {}", path.display()),
         }
     }
 }
 
-// #[cfg(test)]
-// mod tests {
-//     use crate::prelude::*;
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
 
-//     #[test]
-//     fn test_errors() {}
-// }
+    #[test]
+    fn test_errors() {
+        assert_eq!(format!("{}", FileError::FailedToExtractString), "failed to extract string from file");
+        assert_eq!(FileError::failed_to_chmod(vec!["foo", "bar"]), FileError::FailedToChmod(vec![PathBuf::from("foo"), PathBuf::from("bar")]));
+        assert_eq!(format!("{}", FileError::failed_to_chmod(vec!["foo", "bar"])), "failed to change mode for paths: foo, bar");
+        assert_eq!(FileError::invalid_gzip_suffix("foo"), FileError::InvalidGzipSuffix(PathBuf::from("foo")));
+        assert_eq!(format!("{}", FileError::invalid_gzip_suffix("foo")), "gzip tarball must have a .tgz or .tar.gz suffix: foo");
+        let err = FileError::invalid_pattern("(", "(".parse::<Regex>().unwrap_err());
+        assert!(format!("{}", err).starts_with("invalid regex pattern '(': "));
+        assert_eq!(FileError::not_appendable("foo"), FileError::NotAppendable(PathBuf::from("foo")));
+        assert_eq!(format!("{}", FileError::not_appendable("foo")), "archive is compressed and can't be appended to: foo");
+    }
+}
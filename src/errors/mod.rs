@@ -1,14 +1,18 @@
+mod encoding_error;
 mod error;
 mod file_error;
 mod iter_error;
+mod option_error;
 mod os_error;
 mod path_error;
 mod string_error;
 mod user_error;
 
+pub use encoding_error::*;
 pub use error::*;
 pub use file_error::*;
 pub use iter_error::*;
+pub use option_error::*;
 pub use os_error::*;
 pub use path_error::*;
 pub use string_error::*;
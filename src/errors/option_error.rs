@@ -0,0 +1,35 @@
+use std::{error::Error as StdError, fmt};
+
+// An error indicating that something went wrong with an option operation
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum OptionError {
+    /// An error indicating that the option was `None` with the given context message.
+    NoneValue(String),
+}
+impl OptionError {
+    /// Return an error indicating that the option was `None` with the given context message
+    pub fn none_value<T: Into<String>>(msg: T) -> OptionError {
+        OptionError::NoneValue(msg.into())
+    }
+}
+
+impl StdError for OptionError {}
+
+impl fmt::Display for OptionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            OptionError::NoneValue(ref msg) => write!(f, "option was none: {}", msg),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::*;
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(OptionError::none_value("foo"), OptionError::NoneValue("foo".to_string()));
+        assert_eq!(format!("{}", OptionError::none_value("foo")), "option was none: foo");
+    }
+}
@@ -45,6 +45,9 @@ pub enum PathError {
 
     /// An error indicating that the path does not have a valid parent path.
     ParentNotFound(PathBuf),
+
+    /// An error indicating that the path's symlink chain loops or is too deep to resolve.
+    SymlinkLoop(PathBuf),
 }
 impl PathError {
     /// Return an error indicating that the path does not exist
@@ -106,6 +109,11 @@ impl PathError {
     pub fn parent_not_found<T: AsRef<Path>>(path: T) -> PathError {
         PathError::ParentNotFound(path.as_ref().to_path_buf())
     }
+
+    /// Return an error indicating that the path's symlink chain loops or is too deep to resolve
+    pub fn symlink_loop<T: AsRef<Path>>(path: T) -> PathError {
+        PathError::SymlinkLoop(path.as_ref().to_path_buf())
+    }
 }
 
 impl StdError for PathError {}
@@ -132,6 +140,7 @@ impl fmt::Display for PathError {
             PathError::IsNotFileOrSymlinkToFile(ref path) => write!(f, "is not a file or a symlink to a file: {}", path.display()),
             PathError::MultipleHomeSymbols(ref path) => write!(f, "multiple home symbols for path: {}", path.display()),
             PathError::ParentNotFound(ref path) => write!(f, "parent not found for path: {}", path.display()),
+            PathError::SymlinkLoop(ref path) => write!(f, "symlink loop detected for path: {}", path.display()),
         }
     }
 }
@@ -187,6 +196,8 @@ mod tests {
         assert_eq!(format!("{}", PathError::is_not_file_or_symlink_to_file(PathBuf::from("foo"))), "is not a file or a symlink to a file: foo");
         assert_eq!(PathError::multiple_home_symbols(Path::new("foo")), PathError::MultipleHomeSymbols(PathBuf::from("foo")));
         assert_eq!(format!("{}", PathError::multiple_home_symbols(PathBuf::from("foo"))), "multiple home symbols for path: foo");
+        assert_eq!(PathError::symlink_loop(Path::new("foo")), PathError::SymlinkLoop(PathBuf::from("foo")));
+        assert_eq!(format!("{}", PathError::symlink_loop(PathBuf::from("foo"))), "symlink loop detected for path: foo");
     }
 
     #[test]
@@ -5,12 +5,36 @@ use std::{error::Error as StdError, fmt};
 pub enum UserError {
     /// An error indicating that the use does not exist.
     DoesNotExistById(u32),
+
+    /// An error indicating that the user does not exist.
+    DoesNotExistByName(String),
+
+    /// An error indicating that the group does not exist.
+    GroupDoesNotExist(String),
+
+    /// An error indicating that the operation requires root privileges.
+    RequiresRoot,
 }
 impl UserError {
     /// Return an error indicating that the user does not exist
     pub fn does_not_exist_by_id(uid: u32) -> UserError {
         UserError::DoesNotExistById(uid)
     }
+
+    /// Return an error indicating that the user does not exist
+    pub fn does_not_exist_by_name<T: AsRef<str>>(name: T) -> UserError {
+        UserError::DoesNotExistByName(name.as_ref().to_string())
+    }
+
+    /// Return an error indicating that the group does not exist
+    pub fn group_does_not_exist<T: AsRef<str>>(group: T) -> UserError {
+        UserError::GroupDoesNotExist(group.as_ref().to_string())
+    }
+
+    /// Return an error indicating that the operation requires root privileges
+    pub fn requires_root() -> UserError {
+        UserError::RequiresRoot
+    }
 }
 
 impl StdError for UserError {}
@@ -19,6 +43,9 @@ impl fmt::Display for UserError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             UserError::DoesNotExistById(ref uid) => write!(f, "user does not exist: {}", uid),
+            UserError::DoesNotExistByName(ref name) => write!(f, "user does not exist: {}", name),
+            UserError::GroupDoesNotExist(ref group) => write!(f, "group does not exist: {}", group),
+            UserError::RequiresRoot => write!(f, "operation requires root privileges"),
         }
     }
 }
@@ -31,5 +58,11 @@ mod tests {
     fn test_errors() {
         assert_eq!(UserError::does_not_exist_by_id(1000), UserError::DoesNotExistById(1000));
         assert_eq!(format!("{}", UserError::DoesNotExistById(1000)), "user does not exist: 1000");
+        assert_eq!(UserError::does_not_exist_by_name("foo"), UserError::DoesNotExistByName("foo".to_string()));
+        assert_eq!(format!("{}", UserError::does_not_exist_by_name("foo")), "user does not exist: foo");
+        assert_eq!(UserError::group_does_not_exist("foo"), UserError::GroupDoesNotExist("foo".to_string()));
+        assert_eq!(format!("{}", UserError::group_does_not_exist("foo")), "group does not exist: foo");
+        assert_eq!(UserError::requires_root(), UserError::RequiresRoot);
+        assert_eq!(format!("{}", UserError::RequiresRoot), "operation requires root privileges");
     }
 }
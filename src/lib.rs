@@ -22,11 +22,11 @@ pub mod prelude {
         cfgblock,
         core::*,
         defer,
-        enc::{gzip, tar},
+        enc::{base64, gzip, hex, tar},
         errors::*,
-        function,
+        function, function_full, hashmap, hashset,
         net::{self, agent},
-        sys::{self, exec, ext::*, user},
+        sys::{self, edit, exec, ext::*, user},
         unit::{self, time},
     };
 
@@ -122,6 +122,34 @@ macro_rules! function {
     }};
 }
 
+/// Expands to a string literal of the current function's fully qualified name including the
+/// module path, companion to `function!` for when the bare function name isn't enough.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// fn my_func() -> &'static str {
+///     function_full!()
+/// }
+/// assert!(my_func().ends_with("::my_func"));
+/// ```
+#[macro_export]
+macro_rules! function_full {
+    () => {{
+        // Capture the function's type and passes it to `std::any::type_name` to get the
+        // function's fully qualified name, which includes our target.
+        fn _f() {}
+        fn type_of<T>(_: T) -> &'static str {
+            std::any::type_name::<T>()
+        }
+
+        // Trim off the `::_f` suffix
+        let fqn = type_of(_f);
+        &fqn[..fqn.len() - 4]
+    }};
+}
+
 // Unit tests
 // -------------------------------------------------------------------------------------------------
 #[cfg(test)]
@@ -144,4 +172,13 @@ mod tests {
         assert_eq!(function!(), "test_function_macro");
         assert_eq!(indirect_func_name(), "indirect_func_name");
     }
+
+    #[test]
+    fn test_function_full_macro() {
+        fn indirect_func_name() -> &'static str {
+            function_full!()
+        }
+        assert_eq!(function_full!(), "fungus::tests::test_function_full_macro");
+        assert_eq!(indirect_func_name(), "fungus::tests::test_function_full_macro::indirect_func_name");
+    }
 }
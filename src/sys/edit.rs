@@ -0,0 +1,402 @@
+use crate::{
+    errors::*,
+    sys::{self, PathExt},
+};
+use regex::Regex;
+use std::path::Path;
+
+/// Ensure the given `block` of text is in the file `path` between `# BEGIN {marker}` and
+/// `# END {marker}` lines. An existing marked block is replaced while a missing block is appended
+/// to the end of the file. An empty `block` removes the block along with its markers. Returns
+/// `true` if the file was changed. The file's mode is preserved. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("edit_doc_blockinfile");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::write(&file1, "foo\n").is_ok());
+/// assert_eq!(edit::blockinfile(&file1, "fungus", "bar\nbaz").unwrap(), true);
+/// assert_eq!(sys::readstring(&file1).unwrap(), "foo\n# BEGIN fungus\nbar\nbaz\n# END fungus\n");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn blockinfile<T: AsRef<Path>>(path: T, marker: &str, block: &str) -> FuResult<bool> {
+    blockinfile_with(path, marker, block, false)
+}
+
+/// Wraps `blockinfile` backing up the original file to `path.bak` before it is changed. No backup
+/// is created when the block is already up to date.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("edit_doc_blockinfile_b");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::write(&file1, "foo\n").is_ok());
+/// assert_eq!(edit::blockinfile_b(&file1, "fungus", "bar").unwrap(), true);
+/// assert_eq!(sys::readstring(tmpdir.mash("file1.bak")).unwrap(), "foo\n");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn blockinfile_b<T: AsRef<Path>>(path: T, marker: &str, block: &str) -> FuResult<bool> {
+    blockinfile_with(path, marker, block, true)
+}
+
+// Private helper to manage the marked block optionally backing up the original file
+fn blockinfile_with<T: AsRef<Path>>(path: T, marker: &str, block: &str, backup: bool) -> FuResult<bool> {
+    let path = path.as_ref().abs()?;
+    let (lines, newline) = read(&path)?;
+    let begin = format!("# BEGIN {}", marker);
+    let end = format!("# END {}", marker);
+
+    // Build the new marked block if any
+    let mut new = vec![];
+    if !block.is_empty() {
+        new.push(begin.clone());
+        new.extend(block.lines().map(|x| x.to_string()));
+        new.push(end.clone());
+    }
+
+    // Replace the existing block or append the new one
+    let mut result = lines.clone();
+    let start = lines.iter().position(|x| x == &begin);
+    match start.and_then(|i| lines[i..].iter().position(|x| x == &end).map(|j| (i, i + j))) {
+        Some((i, j)) => {
+            result.splice(i..=j, new);
+        },
+        None => result.extend(new),
+    }
+    if result == lines {
+        return Ok(false);
+    }
+    save(&path, join(&result, newline), backup)?;
+    Ok(true)
+}
+
+/// Ensure the given `line` is in the file `path` by replacing the first line matching `regexp`
+/// or appending `line` if there is no match. Returns `true` if the file was changed. The file's
+/// mode and trailing newline are preserved. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("edit_doc_lineinfile");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::write(&file1, "PermitRootLogin yes\n").is_ok());
+/// let rx = Regex::new(r"^PermitRootLogin").unwrap();
+/// assert_eq!(edit::lineinfile(&file1, &rx, "PermitRootLogin no").unwrap(), true);
+/// assert_eq!(sys::readstring(&file1).unwrap(), "PermitRootLogin no\n");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn lineinfile<T: AsRef<Path>>(path: T, regexp: &Regex, line: &str) -> FuResult<bool> {
+    lineinfile_with(path, regexp, line, false)
+}
+
+/// Wraps `lineinfile` backing up the original file to `path.bak` before it is changed. No backup
+/// is created when the line is already present.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("edit_doc_lineinfile_b");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::write(&file1, "foo\n").is_ok());
+/// assert_eq!(edit::lineinfile_b(&file1, &Regex::new(r"^foo").unwrap(), "bar").unwrap(), true);
+/// assert_eq!(sys::readstring(tmpdir.mash("file1.bak")).unwrap(), "foo\n");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn lineinfile_b<T: AsRef<Path>>(path: T, regexp: &Regex, line: &str) -> FuResult<bool> {
+    lineinfile_with(path, regexp, line, true)
+}
+
+// Private helper to ensure the line optionally backing up the original file
+fn lineinfile_with<T: AsRef<Path>>(path: T, regexp: &Regex, line: &str, backup: bool) -> FuResult<bool> {
+    let path = path.as_ref().abs()?;
+    let (mut lines, newline) = read(&path)?;
+    match lines.iter().position(|x| regexp.is_match(x)) {
+        Some(i) if lines[i] == line => return Ok(false),
+        Some(i) => lines[i] = line.to_string(),
+        None if lines.iter().any(|x| x == line) => return Ok(false),
+        None => lines.push(line.to_string()),
+    }
+    save(&path, join(&lines, newline), backup)?;
+    Ok(true)
+}
+
+/// Replace all matches of `regexp` in the file `path` with `replacement` returning the number of
+/// replacements made. The whole file is matched as a single string so patterns may span lines.
+/// Capture groups may be referenced as `$1`, `${name}` or `\1` in the replacement. The file is
+/// left untouched when nothing matches and its mode is preserved. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("edit_doc_replace");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::write(&file1, "#Port 22\n").is_ok());
+/// let rx = Regex::new(r"(?m)^#(Port.*)$").unwrap();
+/// assert_eq!(edit::replace(&file1, &rx, r"\1").unwrap(), 1);
+/// assert_eq!(sys::readstring(&file1).unwrap(), "Port 22\n");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn replace<T: AsRef<Path>>(path: T, regexp: &Regex, replacement: &str) -> FuResult<usize> {
+    replace_with(path, regexp, replacement, false)
+}
+
+/// Wraps `replace` backing up the original file to `path.bak` before it is changed. No backup is
+/// created when nothing changes.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("edit_doc_replace_b");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::write(&file1, "foo\n").is_ok());
+/// assert_eq!(edit::replace_b(&file1, &Regex::new(r"foo").unwrap(), "bar").unwrap(), 1);
+/// assert_eq!(sys::readstring(tmpdir.mash("file1.bak")).unwrap(), "foo\n");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn replace_b<T: AsRef<Path>>(path: T, regexp: &Regex, replacement: &str) -> FuResult<usize> {
+    replace_with(path, regexp, replacement, true)
+}
+
+// Private helper to replace all matches optionally backing up the original file
+fn replace_with<T: AsRef<Path>>(path: T, regexp: &Regex, replacement: &str, backup: bool) -> FuResult<usize> {
+    let path = path.as_ref().abs()?;
+    let data = sys::readstring(&path)?;
+    let count = regexp.find_iter(&data).count();
+    if count > 0 {
+        let result = regexp.replace_all(&data, backrefs(replacement).as_str());
+        if result != data {
+            save(&path, result.as_bytes(), backup)?;
+        }
+    }
+    Ok(count)
+}
+
+// Private helper to convert `\1` style backreferences into the regex crate's `${1}` syntax
+fn backrefs(replacement: &str) -> String {
+    let mut result = String::new();
+    let mut chars = replacement.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some(x)) if x.is_ascii_digit() => {
+                result.push_str("${");
+                while let Some(x) = chars.peek().filter(|x| x.is_ascii_digit()) {
+                    result.push(*x);
+                    chars.next();
+                }
+                result.push('}');
+            },
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+// Private helper to read the lines of the given file and whether it ends with a newline
+fn read(path: &Path) -> FuResult<(Vec<String>, bool)> {
+    let data = sys::readstring(path)?;
+    Ok((sys::readlines(path)?, data.is_empty() || data.ends_with('\n')))
+}
+
+// Private helper to join the given lines adding a trailing newline if requested
+fn join(lines: &[String], newline: bool) -> String {
+    let mut data = lines.join("\n");
+    if newline && !lines.is_empty() {
+        data.push('\n');
+    }
+    data
+}
+
+// Private helper to write the given data to the file preserving its mode and optionally backing
+// up the original file first
+fn save<T: AsRef<[u8]>>(path: &Path, data: T, backup: bool) -> FuResult<()> {
+    let mode = path.mode()?;
+    if backup {
+        sys::backup(path)?;
+    }
+    sys::write(path, data)?;
+    sys::chmod_p(path)?.recurse(false).mode(mode).chmod()?;
+    Ok(())
+}
+
+// Unit tests
+// -------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::backrefs;
+    use crate::prelude::*;
+
+    // Test setup
+    fn setup() -> PathBuf {
+        let temp = PathBuf::from("tests/temp").abs().unwrap();
+        sys::mkdir(&temp).unwrap();
+        temp
+    }
+
+    #[test]
+    fn test_backup() {
+        let tmpdir = setup().mash("edit_backup");
+        let file1 = tmpdir.mash("file1");
+        let backup1 = tmpdir.mash("file1.bak");
+        let rx = Regex::new(r"^foo").unwrap();
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::write_p(&file1, "foo\n", 0o600).is_ok());
+
+        // no backup on a no-op edit
+        assert!(!edit::lineinfile_b(&file1, &rx, "foo").unwrap());
+        assert!(!edit::blockinfile_b(&file1, "fungus", "").unwrap());
+        assert_eq!(edit::replace_b(&file1, &Regex::new(r"bar").unwrap(), "baz").unwrap(), 0);
+        assert!(!backup1.exists());
+
+        // backup matches the pre-edit contents
+        assert!(edit::lineinfile_b(&file1, &rx, "foo = 1").unwrap());
+        assert_eq!(sys::readstring(&backup1).unwrap(), "foo\n");
+        assert_eq!(backup1.mode().unwrap(), 0o100600);
+        assert_eq!(sys::readstring(&file1).unwrap(), "foo = 1\n");
+
+        // subsequent backups don't overwrite previous ones
+        assert!(edit::blockinfile_b(&file1, "fungus", "bar").unwrap());
+        assert_eq!(edit::replace_b(&file1, &Regex::new(r"bar").unwrap(), "baz").unwrap(), 1);
+        assert_eq!(sys::readstring(&backup1).unwrap(), "foo\n");
+        let backups = sys::read_dir_filtered(&tmpdir, |x| x.has_suffix(".bak")).unwrap();
+        assert_eq!(backups.len(), 3);
+        let mut contents: Vec<String> = backups.iter().map(|x| sys::readstring(x).unwrap()).collect();
+        contents.sort();
+        assert_eq!(contents, vec!["foo\n".to_string(), "foo = 1\n".to_string(), "foo = 1\n# BEGIN fungus\nbar\n# END fungus\n".to_string()]);
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_blockinfile() {
+        let tmpdir = setup().mash("edit_blockinfile");
+        let file1 = tmpdir.mash("file1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::write_p(&file1, "foo\nbar\n", 0o640).is_ok());
+
+        // removing a missing block is a no-op
+        assert!(!edit::blockinfile(&file1, "fungus", "").unwrap());
+
+        // initial insert appends the block preserving mode
+        assert!(edit::blockinfile(&file1, "fungus", "one\ntwo").unwrap());
+        assert_eq!(sys::readstring(&file1).unwrap(), "foo\nbar\n# BEGIN fungus\none\ntwo\n# END fungus\n");
+        assert_eq!(file1.mode().unwrap(), 0o100640);
+
+        // re-insert is idempotent
+        assert!(!edit::blockinfile(&file1, "fungus", "one\ntwo").unwrap());
+        assert!(edit::lineinfile(&file1, &Regex::new(r"^baz").unwrap(), "baz").unwrap());
+
+        // update replaces the block in place
+        assert!(edit::blockinfile(&file1, "fungus", "three").unwrap());
+        assert_eq!(sys::readstring(&file1).unwrap(), "foo\nbar\n# BEGIN fungus\nthree\n# END fungus\nbaz\n");
+
+        // other markers are independent
+        assert!(edit::blockinfile(&file1, "other", "four").unwrap());
+        assert_eq!(sys::readstring(&file1).unwrap(), "foo\nbar\n# BEGIN fungus\nthree\n# END fungus\nbaz\n# BEGIN other\nfour\n# END other\n");
+
+        // removal drops the block and its markers
+        assert!(edit::blockinfile(&file1, "fungus", "").unwrap());
+        assert_eq!(sys::readstring(&file1).unwrap(), "foo\nbar\nbaz\n# BEGIN other\nfour\n# END other\n");
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_lineinfile() {
+        let tmpdir = setup().mash("edit_lineinfile");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("file2");
+        let rx = Regex::new(r"^#?PermitRootLogin").unwrap();
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::write_p(&file1, "Port 22\n#PermitRootLogin yes\nPermitRootLogin yes\n", 0o600).is_ok());
+
+        // replace the first match preserving mode
+        assert!(edit::lineinfile(&file1, &rx, "PermitRootLogin no").unwrap());
+        assert_eq!(sys::readstring(&file1).unwrap(), "Port 22\nPermitRootLogin no\nPermitRootLogin yes\n");
+        assert_eq!(file1.mode().unwrap(), 0o100600);
+
+        // no-op when the line is already present
+        assert!(!edit::lineinfile(&file1, &rx, "PermitRootLogin no").unwrap());
+        assert!(!edit::lineinfile(&file1, &Regex::new(r"^Foo").unwrap(), "Port 22").unwrap());
+        assert_eq!(sys::readstring(&file1).unwrap(), "Port 22\nPermitRootLogin no\nPermitRootLogin yes\n");
+
+        // append when there is no match
+        assert!(edit::lineinfile(&file1, &Regex::new(r"^UseDNS").unwrap(), "UseDNS no").unwrap());
+        assert_eq!(sys::readstring(&file1).unwrap(), "Port 22\nPermitRootLogin no\nPermitRootLogin yes\nUseDNS no\n");
+
+        // files without a trailing newline or content
+        assert!(sys::write(&file2, "foo").is_ok());
+        assert!(edit::lineinfile(&file2, &Regex::new(r"^bar").unwrap(), "bar").unwrap());
+        assert_eq!(sys::readstring(&file2).unwrap(), "foo\nbar");
+        assert!(sys::write(&file2, "").is_ok());
+        assert!(edit::lineinfile(&file2, &Regex::new(r"^bar").unwrap(), "bar").unwrap());
+        assert_eq!(sys::readstring(&file2).unwrap(), "bar\n");
+
+        // doesn't exist
+        assert!(edit::lineinfile(tmpdir.mash("foo"), &rx, "foo").is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_replace() {
+        let tmpdir = setup().mash("edit_replace");
+        let file1 = tmpdir.mash("file1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::write_p(&file1, "#Port 22\n#UseDNS yes\n[section]\nfoo = 1\n", 0o600).is_ok());
+
+        // backreferences
+        assert_eq!(backrefs(r"\1 $2 \12 \x \"), "${1} $2 ${12} \\x \\");
+        assert_eq!(edit::replace(&file1, &Regex::new(r"(?m)^#(\w+) (.*)$").unwrap(), r"\1 = \2").unwrap(), 2);
+        assert_eq!(sys::readstring(&file1).unwrap(), "Port = 22\nUseDNS = yes\n[section]\nfoo = 1\n");
+        assert_eq!(file1.mode().unwrap(), 0o100600);
+
+        // multi-line matches
+        assert_eq!(edit::replace(&file1, &Regex::new(r"\[section\]\nfoo = (\d)").unwrap(), "[section]\nfoo = ${1}0").unwrap(), 1);
+        assert_eq!(sys::readstring(&file1).unwrap(), "Port = 22\nUseDNS = yes\n[section]\nfoo = 10\n");
+
+        // zero replacements leave the file untouched
+        let before = sys::readbytes(&file1).unwrap();
+        let modified = file1.modified().unwrap();
+        assert_eq!(edit::replace(&file1, &Regex::new(r"^bar").unwrap(), "baz").unwrap(), 0);
+        assert_eq!(sys::readbytes(&file1).unwrap(), before);
+        assert_eq!(file1.modified().unwrap(), modified);
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+}
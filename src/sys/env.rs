@@ -1,7 +1,8 @@
 use crate::{errors::*, sys::PathExt};
 use std::{
+    collections::HashMap,
     env,
-    ffi::OsStr,
+    ffi::{OsStr, OsString},
     io,
     path::{Path, PathBuf},
 };
@@ -52,6 +53,78 @@ pub fn cwd() -> io::Result<PathBuf> {
     env::current_dir()
 }
 
+/// Changes the current working directory to the given `dir` returning a guard that restores the
+/// original working directory when dropped, even on panic. Handles path expansion.
+///
+/// ### Examples
+/// ```rust
+/// use fungus::prelude::*;
+///
+/// let cwd = sys::cwd().unwrap();
+/// {
+///     let _guard = sys::cwd_guard("/").unwrap();
+///     assert_eq!(sys::cwd().unwrap(), PathBuf::from("/"));
+/// }
+/// assert_eq!(sys::cwd().unwrap(), cwd);
+/// ```
+pub fn cwd_guard<T: AsRef<Path>>(dir: T) -> FuResult<CwdGuard> {
+    let guard = CwdGuard { cwd: cwd()? };
+    set_cwd(dir)?;
+    Ok(guard)
+}
+
+/// Restores the original working directory when dropped. See [`cwd_guard`].
+pub struct CwdGuard {
+    cwd: PathBuf,
+}
+
+impl Drop for CwdGuard {
+    fn drop(&mut self) {
+        let _ = env::set_current_dir(&self.cwd);
+    }
+}
+
+/// Restores the environment of the current process to the given snapshot. Every variable in the
+/// snapshot is set and any variable currently set but absent from the snapshot is removed.
+/// Variables with a key or value that isn't valid unicode can't be represented in a snapshot so
+/// they are left alone.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let snap = sys::env_snapshot();
+/// sys::set_var("ENV_RESTORE_DOC", "foo");
+/// sys::env_restore(&snap);
+/// assert!(sys::var("ENV_RESTORE_DOC").is_err());
+/// ```
+pub fn env_restore(snap: &HashMap<String, String>) {
+    for (key, val) in env::vars_os() {
+        if let (Some(x), Some(_)) = (key.to_str(), val.to_str()) {
+            if !snap.contains_key(x) {
+                unset_var(&key);
+            }
+        }
+    }
+    for (key, val) in snap {
+        set_var(key, val);
+    }
+}
+
+/// Returns a snapshot of all the environment variables of the current process for use with
+/// [`env_restore`]. Variables with a key or value that isn't valid unicode are skipped.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let snap = sys::env_snapshot();
+/// assert_eq!(snap.get("HOME"), sys::var("HOME").ok().as_ref());
+/// ```
+pub fn env_snapshot() -> HashMap<String, String> {
+    env::vars_os().filter_map(|(key, val)| Some((key.into_string().ok()?, val.into_string().ok()?))).collect()
+}
+
 /// Returns the full filesystem path of the current running executable.
 /// Wraps std::env::current_exec
 ///
@@ -291,6 +364,41 @@ pub fn var<K: AsRef<OsStr>>(key: K) -> std::result::Result<String, env::VarError
     env::var(key)
 }
 
+/// Sets the environment variable `key` to the value `val` returning a guard that restores the
+/// previous value when dropped, or removes the variable if it wasn't previously set.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// sys::unset_var("VAR_GUARD_DOC");
+/// {
+///     let _guard = sys::var_guard("VAR_GUARD_DOC", "foo");
+///     assert_eq!(sys::var("VAR_GUARD_DOC"), Ok("foo".to_string()));
+/// }
+/// assert!(sys::var("VAR_GUARD_DOC").is_err());
+/// ```
+pub fn var_guard<K: AsRef<OsStr>, V: AsRef<OsStr>>(key: K, val: V) -> VarGuard {
+    let guard = VarGuard { key: key.as_ref().to_os_string(), val: env::var_os(&key) };
+    set_var(key, val);
+    guard
+}
+
+/// Restores the previous value of an environment variable when dropped. See [`var_guard`].
+pub struct VarGuard {
+    key: OsString,
+    val: Option<OsString>,
+}
+
+impl Drop for VarGuard {
+    fn drop(&mut self) {
+        match self.val {
+            Some(ref val) => set_var(&self.key, val),
+            None => unset_var(&self.key),
+        }
+    }
+}
+
 /// Returns an iterator of (variable, value) pairs of strings, for all the
 /// environment variables of the current process.
 /// Wraps std::env::vars
@@ -350,6 +458,32 @@ mod tests {
         assert!(sys::flag("FLAG"));
     }
 
+    #[test]
+    fn test_var_guard() {
+        sys::unset_var("VAR_GUARD");
+
+        // unset variable is removed again
+        {
+            let _guard = sys::var_guard("VAR_GUARD", "foo");
+            assert_eq!(sys::var("VAR_GUARD"), Ok("foo".to_string()));
+        }
+        assert!(sys::var("VAR_GUARD").is_err());
+
+        // set variable is restored including nested guards
+        sys::set_var("VAR_GUARD", "bar");
+        {
+            let _guard1 = sys::var_guard("VAR_GUARD", "foo1");
+            assert_eq!(sys::var("VAR_GUARD"), Ok("foo1".to_string()));
+            {
+                let _guard2 = sys::var_guard("VAR_GUARD", "foo2");
+                assert_eq!(sys::var("VAR_GUARD"), Ok("foo2".to_string()));
+            }
+            assert_eq!(sys::var("VAR_GUARD"), Ok("foo1".to_string()));
+        }
+        assert_eq!(sys::var("VAR_GUARD"), Ok("bar".to_string()));
+        sys::unset_var("VAR_GUARD");
+    }
+
     #[test]
     fn test_flag_defaultg() {
         sys::unset_var("FOOBAR");
@@ -4,7 +4,11 @@ use crate::{
     sys::{self, user, PathExt},
     FuResult,
 };
-use std::path::{Path, PathBuf};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
 
 /// Returns the full path to the directory of the current running executable.
 ///
@@ -42,17 +46,67 @@ pub fn lookup<T: AsRef<Path>>(target: T) -> FuResult<PathBuf> {
         },
 
         // Target is a name
-        false => {
-            let base = path.to_string()?;
-            for dir in user::path_dirs()? {
-                let path = sys::mash(dir, &base);
-                if !path.is_dir() && path.is_exec() {
-                    return Ok(path);
-                }
-            }
-            Err(PathError::does_not_exist(target).into())
-        },
+        false => lookup_dirs(path, user::path_dirs()?),
+    }
+}
+
+/// Returns the full paths of every executable named `name` found in the system `PATH`. Paths are
+/// returned in `PATH` order preserving duplicates. Unlike `lookup` an empty vector is returned
+/// rather than an error when nothing is found.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert!(exec::lookup_all("fungus_doc_lookup_all").unwrap().is_empty());
+/// ```
+pub fn lookup_all<T: AsRef<Path>>(name: T) -> FuResult<Vec<PathBuf>> {
+    lookup_all_dirs(name, user::path_dirs()?)
+}
+
+// Private helper to search the given directories in order for all executable matches
+fn lookup_all_dirs<T: AsRef<Path>>(name: T, dirs: Vec<PathBuf>) -> FuResult<Vec<PathBuf>> {
+    let base = name.as_ref().to_string()?;
+    let mut paths = Vec::new();
+    for dir in dirs {
+        let path = sys::mash(dir, &base);
+        if !path.is_dir() && path.is_exec() {
+            paths.push(path.abs()?);
+        }
+    }
+    Ok(paths)
+}
+
+/// Returns the full path of the given executable `name` searching only the colon separated
+/// directories of the given `path` string rather than the system `PATH`. Directories are
+/// searched in order and the first executable match is returned.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("exec_doc_lookup_in");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::touch_p(&file1, 0o755).is_ok());
+/// assert_eq!(exec::lookup_in("file1", tmpdir.to_string().unwrap()).unwrap(), file1);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn lookup_in<T: AsRef<Path>, U: AsRef<str>>(name: T, path: U) -> FuResult<PathBuf> {
+    lookup_dirs(name, sys::parse_paths(path)?)
+}
+
+// Private helper to search the given directories in order for the first executable match
+fn lookup_dirs<T: AsRef<Path>>(name: T, dirs: Vec<PathBuf>) -> FuResult<PathBuf> {
+    let base = name.as_ref().to_string()?;
+    for dir in dirs {
+        let path = sys::mash(dir, &base);
+        if !path.is_dir() && path.is_exec() {
+            return Ok(path);
+        }
     }
+    Err(PathError::does_not_exist(name).into())
 }
 
 /// Returns the current running executable's name.
@@ -68,10 +122,43 @@ pub fn name() -> FuResult<String> {
     Ok(sys::exe()?.base()?)
 }
 
+/// Run the command given by `args` in the working directory `dir` returning its output. The
+/// given `env` overrides are applied on top of the inherited environment, an empty `env` leaves
+/// the inherited environment untouched. The `dir` path is expanded and made absolute first.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap();
+/// let out = exec::run_in(&["pwd"], &tmpdir, &[]).unwrap();
+/// assert_eq!(String::from_utf8_lossy(&out.stdout).trim_end(), tmpdir.to_string().unwrap());
+/// ```
+pub fn run_in<T: AsRef<str>, U: AsRef<Path>>(args: &[T], dir: U, env: &[(String, String)]) -> FuResult<Output> {
+    if args.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "command must not be empty").into());
+    }
+    let dir = dir.as_ref().abs()?;
+    let mut cmd = Command::new(args[0].as_ref());
+    cmd.args(args[1..].iter().map(|x| x.as_ref())).current_dir(&dir);
+    for (key, val) in env {
+        cmd.env(key, val);
+    }
+    Ok(cmd.output()?)
+}
+
 #[cfg(test)]
 mod tests {
+    use super::lookup_all_dirs;
     use crate::prelude::*;
 
+    // Test setup
+    fn setup() -> PathBuf {
+        let temp = PathBuf::from("tests/temp").abs().unwrap();
+        sys::mkdir(&temp).unwrap();
+        temp
+    }
+
     #[test]
     fn test_dir() {
         let cwd = sys::cwd().unwrap();
@@ -102,10 +189,97 @@ mod tests {
     //     assert!(sys::remove_all(&tmpdir).is_ok());
     // }
 
+    #[test]
+    fn test_lookup_all() {
+        let tmpdir = setup().mash("exec_lookup_all");
+        let dir1 = tmpdir.mash("dir1");
+        let dir2 = tmpdir.mash("dir2");
+        let dir3 = tmpdir.mash("dir3");
+        let file1 = dir1.mash("file1");
+        let file2 = dir2.mash("file1");
+        let file3 = dir3.mash("file1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::mkdir(&dir2).is_ok());
+        assert!(sys::mkdir(&dir3).is_ok());
+        assert!(sys::touch_p(&file1, 0o755).is_ok());
+        assert!(sys::touch_p(&file2, 0o755).is_ok());
+        assert!(sys::touch_p(&file3, 0o644).is_ok());
+
+        // all matches in order preserving duplicates
+        let dirs = vec![dir2.clone(), dir3.clone(), dir1.clone(), dir2.clone()];
+        assert_eq!(lookup_all_dirs("file1", dirs).unwrap(), vec![file2.clone(), file1, file2]);
+
+        // nothing found is not an error
+        assert!(lookup_all_dirs("foo", vec![dir1, dir2, dir3]).unwrap().is_empty());
+        assert!(exec::lookup_all("fungus_lookup_all_foo").unwrap().is_empty());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_lookup_in() {
+        let tmpdir = setup().mash("exec_lookup_in");
+        let dir1 = tmpdir.mash("dir1");
+        let dir2 = tmpdir.mash("dir2");
+        let file1 = dir1.mash("file1");
+        let file2 = dir2.mash("file1");
+        let file3 = dir2.mash("file3");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::mkdir(&dir2).is_ok());
+        assert!(sys::touch_p(&file1, 0o755).is_ok());
+        assert!(sys::touch_p(&file2, 0o755).is_ok());
+        assert!(sys::touch_p(&file3, 0o644).is_ok());
+
+        // first dir in the path wins
+        let path = format!("{}:{}", dir1.to_string().unwrap(), dir2.to_string().unwrap());
+        assert_eq!(exec::lookup_in("file1", &path).unwrap(), file1);
+        let path = format!("{}:{}", dir2.to_string().unwrap(), dir1.to_string().unwrap());
+        assert_eq!(exec::lookup_in("file1", &path).unwrap(), file2);
+
+        // not executable or not found
+        assert!(exec::lookup_in("file3", &path).is_err());
+        assert!(exec::lookup_in("foo", &path).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_name() {
         let exec_path = sys::exe().unwrap();
         let name = exec_path.base().unwrap();
         assert_eq!(name, exec::name().unwrap());
     }
+
+    #[test]
+    fn test_run_in() {
+        let tmpdir = setup().mash("exec_run_in");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // working directory
+        let out = exec::run_in(&["pwd"], &tmpdir, &[]).unwrap();
+        assert!(out.status.success());
+        assert_eq!(String::from_utf8_lossy(&out.stdout).trim_end(), tmpdir.to_string().unwrap());
+
+        // environment overrides on top of the inherited environment
+        let env = vec![("FUNGUS_RUN_IN".to_string(), "foo".to_string())];
+        let out = exec::run_in(&["sh", "-c", "echo $FUNGUS_RUN_IN:$HOME"], &tmpdir, &env).unwrap();
+        assert_eq!(String::from_utf8_lossy(&out.stdout).trim_end(), format!("foo:{}", sys::var("HOME").unwrap()));
+
+        // empty command
+        assert!(exec::run_in::<&str, _>(&[], &tmpdir, &[]).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
 }
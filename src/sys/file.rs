@@ -1,27 +1,179 @@
 use crate::{
     errors::*,
-    sys::{self, PathExt},
+    sys::{self, user, PathExt},
+    unit::time,
     FuResult,
 };
 use blake2::{Blake2b, Digest};
 use regex::Regex;
 use std::{
+    collections::VecDeque,
     ffi::CString,
-    fs::{self, File},
+    fmt,
+    fs::{self, File, OpenOptions},
     io::{self, prelude::*, BufRead, BufReader},
-    os::unix::{self, ffi::OsStrExt, fs::PermissionsExt},
+    iter,
+    os::unix::{
+        self,
+        ffi::OsStrExt,
+        fs::{DirBuilderExt, MetadataExt, OpenOptionsExt, PermissionsExt},
+    },
     path::{Path, PathBuf},
 };
 use walkdir::WalkDir;
 
+/// Append `[u8]` data to a file which means `str` or `String`, creating the file if it doesn't
+/// exist. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_append");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::append(&tmpfile, "foo").is_ok());
+/// assert!(sys::append(&tmpfile, "bar").is_ok());
+/// assert_eq!(sys::readstring(&tmpfile).unwrap(), "foobar");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn append<T: AsRef<Path>, U: AsRef<[u8]>>(path: T, data: U) -> FuResult<()> {
+    let path = path.as_ref().abs()?;
+    let mut f = OpenOptions::new().create(true).append(true).open(path)?;
+    f.write_all(data.as_ref())?;
+
+    // f.sync_all() works better than f.flush()?
+    f.sync_all()?;
+    Ok(())
+}
+
+/// Wraps `append` allowing for setting the file's mode when the file is created. Existing files
+/// keep their current mode.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_append_p");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::append_p(&tmpfile, "this is a test", 0o666).is_ok());
+/// assert_eq!(sys::readstring(&tmpfile).unwrap(), "this is a test");
+/// assert_eq!(tmpfile.mode().unwrap(), 0o100666);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn append_p<T: AsRef<Path>, U: AsRef<[u8]>>(path: T, data: U, mode: u32) -> FuResult<()> {
+    let path = path.as_ref().abs()?;
+    let exists = path.exists();
+    append(&path, data)?;
+    if !exists {
+        chmod_p(&path)?.recurse(false).mode(mode).chmod()?;
+    }
+    Ok(())
+}
+
+/// Append `&[String]` data to a file as newline terminated lines, creating the file if it doesn't
+/// exist. A missing trailing newline on the existing content, as left by `writelines`, is added
+/// first so that lines never run together. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_appendlines");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::appendlines(&tmpfile, &[String::from("one")]).is_ok());
+/// assert!(sys::appendlines(&tmpfile, &[String::from("two")]).is_ok());
+/// assert_eq!(sys::readstring(&tmpfile).unwrap(), "one\ntwo\n");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn appendlines<T: AsRef<Path>>(path: T, lines: &[String]) -> FuResult<()> {
+    let path = path.as_ref().abs()?;
+    let mut data = String::new();
+    if path.is_file() {
+        let len = path.metadata()?.len();
+        if len > 0 && readchunk(&path, len - 1, 1)? != b"\n" {
+            data.push('\n');
+        }
+    }
+    for line in lines {
+        data.push_str(line);
+        data.push('\n');
+    }
+    append(&path, data)
+}
+
+/// Copies the given file `path` to `path.bak` preserving mode and times and returning the absolute
+/// path of the backup. If `path.bak` already exists a timestamped `path.<timestamp>.bak` backup
+/// is created instead so that previous backups are never overwritten. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_backup");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::write(&file1, "this is a test").is_ok());
+/// assert_eq!(sys::backup(&file1).unwrap(), tmpdir.mash("file1.bak"));
+/// assert_eq!(sys::readstring(tmpdir.mash("file1.bak")).unwrap(), "this is a test");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn backup<T: AsRef<Path>>(path: T) -> FuResult<PathBuf> {
+    let path = path.as_ref().abs()?;
+    if !path.exists() {
+        return Err(PathError::does_not_exist(&path).into());
+    }
+    if !path.is_file() {
+        return Err(PathError::is_not_file(&path).into());
+    }
+
+    // Pick a backup name that doesn't exist yet
+    let mut dst = path.concat(".bak")?;
+    if dst.exists() {
+        let stamp = time::Local::now().format("%Y%m%d%H%M%S");
+        dst = path.concat(format!(".{}.bak", stamp))?;
+        let mut i = 1;
+        while dst.exists() {
+            dst = path.concat(format!(".{}.{}.bak", stamp, i))?;
+            i += 1;
+        }
+    }
+
+    copyfile(&path, &dst)?;
+    copy_times(&path, &dst)?;
+    Ok(dst)
+}
+
+// Private helper to set the access and modification times of `dst` to those of `src`
+fn copy_times<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<()> {
+    let meta = fs::metadata(src.as_ref())?;
+    let times = [
+        libc::timespec { tv_sec: meta.atime() as libc::time_t, tv_nsec: meta.atime_nsec() as libc::c_long },
+        libc::timespec { tv_sec: meta.mtime() as libc::time_t, tv_nsec: meta.mtime_nsec() as libc::c_long },
+    ];
+    let path = CString::new(dst.as_ref().as_os_str().as_bytes())?;
+    if unsafe { libc::utimensat(libc::AT_FDCWD, path.as_ptr(), times.as_ptr(), 0) } != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
 /// Chmod provides flexible options for changing file permission with optional configuration.
 #[derive(Debug, Clone)]
 pub struct Chmod {
-    mode: u32,       // mode to use
-    path: PathBuf,   // path to chmod
-    dirs: bool,      // chmod only dirs
-    files: bool,     // chmod only files
-    recursive: bool, // chmod recursively
+    mode: u32,         // mode to use
+    path: PathBuf,     // path to chmod
+    dirs: bool,        // chmod only dirs
+    files: bool,       // chmod only files
+    recursive: bool,   // chmod recursively
+    best_effort: bool, // continue past failures
+    scripts: bool,     // only add execute to shebang files
 }
 
 impl Chmod {
@@ -112,9 +264,35 @@ impl Chmod {
         self
     }
 
+    /// Update the `best_effort` option. Default is disabled.
+    /// When `yes` is `true`, failures to change individual paths are collected rather than
+    /// stopping at the first failure and an aggregate error listing them is returned at the end.
+    pub fn best_effort(&mut self, yes: bool) -> &mut Self {
+        self.best_effort = yes;
+        self
+    }
+
+    /// Update the `only_scripts` option. Default is disabled.
+    /// When `yes` is `true`, the set `mode` is ignored and the execute bit is added only to files
+    /// starting with a `#!` shebang leaving directories and all other files untouched.
+    pub fn only_scripts(&mut self, yes: bool) -> &mut Self {
+        self.scripts = yes;
+        self
+    }
+
     /// Execute the [`Chmod`] options against the set `path` with the set `mode`.
-    #[allow(clippy::all)]
     pub fn chmod(&self) -> FuResult<()> {
+        let mut failed: Vec<PathBuf> = Vec::new();
+        self.chmod_all(&mut failed)?;
+        if !failed.is_empty() {
+            return Err(FileError::failed_to_chmod(failed).into());
+        }
+        Ok(())
+    }
+
+    // Private implementation of chmod tracking failed paths when in best effort mode
+    #[allow(clippy::all)]
+    fn chmod_all(&self, failed: &mut Vec<PathBuf>) -> FuResult<()> {
         // Handle globbing
         let sources = sys::glob(&self.path)?;
         if sources.is_empty() {
@@ -123,34 +301,76 @@ impl Chmod {
 
         // Execute the chmod for all sources
         for source in sources {
-            let (is_dir, old_mode) = match self.dirs || self.files || self.recursive {
-                true => (source.is_dir(), source.mode()?),
+            let (is_dir, old_mode) = match self.dirs || self.files || self.recursive || self.scripts {
+                true => match source.mode() {
+                    Ok(mode) => (source.is_dir(), mode),
+                    Err(err) => {
+                        self.failed(&source, err, failed)?;
+                        continue;
+                    },
+                },
                 false => (false, 0),
             };
 
+            // Add execute permission to scripts only
+            if self.scripts && !is_dir && is_script(&source) {
+                if let Err(err) = source.setperms(fs::Permissions::from_mode(old_mode | 0o111)) {
+                    self.failed(&source, err, failed)?;
+                }
+            }
+
             // Grant permissions on the way in
-            if (!self.dirs && !self.files) || (self.dirs && is_dir) || (self.files && !is_dir) {
+            if !self.scripts && ((!self.dirs && !self.files) || (self.dirs && is_dir) || (self.files && !is_dir)) {
                 if !self.recursive || !is_dir || !revoking_mode(old_mode, self.mode) {
-                    source.setperms(fs::Permissions::from_mode(self.mode))?;
+                    if let Err(err) = source.setperms(fs::Permissions::from_mode(self.mode)) {
+                        self.failed(&source, err, failed)?;
+                    }
                 }
             }
 
             // Handle recursion
             if self.recursive && is_dir {
-                for path in sys::paths(&source)? {
-                    self.clone().path(path).chmod()?;
+                match sys::paths(&source) {
+                    Ok(paths) => {
+                        for path in paths {
+                            self.clone().path(path).chmod_all(failed)?;
+                        }
+                    },
+                    Err(err) => self.failed(&source, err, failed)?,
                 }
             }
 
             // Revoke permissions on the way out
-            if (!self.dirs && !self.files) || (self.dirs && is_dir) || (self.files && !is_dir) {
+            if !self.scripts && ((!self.dirs && !self.files) || (self.dirs && is_dir) || (self.files && !is_dir)) {
                 if self.recursive && is_dir && revoking_mode(old_mode, self.mode) {
-                    source.setperms(fs::Permissions::from_mode(self.mode))?;
+                    if let Err(err) = source.setperms(fs::Permissions::from_mode(self.mode)) {
+                        self.failed(&source, err, failed)?;
+                    }
                 }
             }
         }
         Ok(())
     }
+
+    // Private helper to track the failed path in best effort mode else return the error
+    fn failed(&self, path: &Path, err: FuError, failed: &mut Vec<PathBuf>) -> FuResult<()> {
+        match self.best_effort {
+            true => {
+                failed.push(path.to_path_buf());
+                Ok(())
+            },
+            false => Err(err),
+        }
+    }
+}
+
+// Private helper to check if the given file starts with a `#!` shebang
+fn is_script(path: &Path) -> bool {
+    let mut buffer = [0; 2];
+    match File::open(path) {
+        Ok(mut f) => f.read_exact(&mut buffer).is_ok() && &buffer == b"#!",
+        Err(_) => false,
+    }
 }
 
 /// Wraps `chmod_p` to apply the given `mode` to all files/dirs using recursion and invoking
@@ -201,7 +421,7 @@ pub fn chmod_p<T: AsRef<Path>>(path: T) -> FuResult<Chmod> {
         Ok(x) => x,
         _ => 0o644,
     };
-    Ok(Chmod { path, mode, dirs: false, files: false, recursive: true })
+    Ok(Chmod { path, mode, dirs: false, files: false, recursive: true, best_effort: false, scripts: false })
 }
 
 /// Change the ownership of the `path` providing path expansion, globbing, recursion and error
@@ -222,6 +442,35 @@ pub fn chown<T: AsRef<Path>>(path: T, uid: u32, gid: u32) -> FuResult<()> {
     chown_p(path, uid, gid, true)
 }
 
+/// Change the ownership of the `path` to the given `user` and `group` names providing path
+/// expansion, globbing, recursion and error tracing. An empty `user` or `group` leaves the
+/// respective id unchanged. Follows links.
+///
+/// ### Examples
+/// ```ignore
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_chown_by_name");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::touch(&file1).is_ok());
+/// assert!(sys::chown_by_name(&file1, "nginx", "nginx").is_ok());
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn chown_by_name<T: AsRef<Path>>(path: T, user: &str, group: &str) -> FuResult<()> {
+    // (uid_t)-1 and (gid_t)-1 tell chown to leave the respective id unchanged
+    let uid = match user {
+        "" => u32::MAX,
+        _ => user::lookup_by_name(user)?.uid,
+    };
+    let gid = match group {
+        "" => u32::MAX,
+        _ => user::group(group)?.gid,
+    };
+    chown(path, uid, gid)
+}
+
 /// Change the ownership of the `path` providing path expansion, globbing, recursion and error
 /// tracing. Does not follow links.
 //// ### Examples
@@ -253,164 +502,525 @@ fn chown_p<T: AsRef<Path>>(path: T, uid: u32, gid: u32, follow: bool) -> FuResul
     // Execute the chmod for all sources
     for source in sources {
         for entry in WalkDir::new(&source).follow_links(false).sort_by(|x, y| x.file_name().cmp(y.file_name())) {
-            let srcpath = entry?.into_path();
-            let osstr = CString::new(srcpath.as_os_str().as_bytes())?;
-            let ret = unsafe {
-                if follow {
-                    libc::chown(osstr.as_ptr(), uid, gid)
-                } else {
-                    libc::lchown(osstr.as_ptr(), uid, gid)
-                }
-            };
-            if ret != 0 {
-                return Err(io::Error::last_os_error().into());
-            }
+            chown_one(entry?.path(), uid, gid, follow)?;
         }
     }
     Ok(())
 }
 
-/// Copies src to dst recursively creating destination directories as needed and handling path
-/// expansion and globbing e.g. copy("./*", "../") and returning an absolute path of the
-/// destination.
-///
-/// The dst will be copied to if it is an existing directory.
-/// The dst will be a clone of the src if it doesn't exist.
-/// Doesn't follow links
+// Private helper to change the ownership of a single path
+fn chown_one(path: &Path, uid: u32, gid: u32, follow: bool) -> FuResult<()> {
+    let osstr = CString::new(path.as_os_str().as_bytes())?;
+    let ret = unsafe {
+        if follow {
+            libc::chown(osstr.as_ptr(), uid, gid)
+        } else {
+            libc::lchown(osstr.as_ptr(), uid, gid)
+        }
+    };
+    if ret != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
+/// Returns an iterator over the content of the given file in successive blocks of `size` bytes,
+/// the last block possibly being shorter. Only one block is buffered at a time making this
+/// suitable for streaming large files. Handles path expansion.
 ///
 /// ### Examples
 /// ```
 /// use fungus::prelude::*;
 ///
-/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_copy");
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_chunks");
 /// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
 /// assert!(sys::mkdir(&tmpdir).is_ok());
-/// let file1 = tmpdir.mash("file1");
-/// let file2 = tmpdir.mash("file2");
-/// assert!(sys::touch(&file1).is_ok());
-/// assert!(sys::copy(&file1, &file2).is_ok());
-/// assert_eq!(file2.exists(), true);
+/// assert!(sys::write(&tmpfile, "abcdef").is_ok());
+/// let chunks = sys::chunks(&tmpfile, 4).unwrap().collect::<FuResult<Vec<Vec<u8>>>>().unwrap();
+/// assert_eq!(chunks, vec![b"abcd".to_vec(), b"ef".to_vec()]);
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// ```
-pub fn copy<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<PathBuf> {
-    let mut clone = true;
-    let dstabs = dst.as_ref().abs()?;
-
-    // Handle globbing
-    let sources = sys::glob(&src)?;
-    if sources.is_empty() {
-        return Err(PathError::does_not_exist(&src).into());
-    }
-
-    // Copy into destination vs clone as destination
-    if dstabs.is_dir() || sources.len() > 1 {
-        clone = false;
+pub fn chunks<T: AsRef<Path>>(path: T, size: usize) -> FuResult<impl Iterator<Item = FuResult<Vec<u8>>>> {
+    if size == 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "chunk size must be greater than zero").into());
     }
+    let mut f = File::open(path.as_ref().abs()?)?;
+    let mut done = false;
+    Ok(iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        let mut chunk = Vec::with_capacity(size);
+        match (&mut f).take(size as u64).read_to_end(&mut chunk) {
+            Ok(0) => None,
+            Ok(n) => {
+                done = n < size;
+                Some(Ok(chunk))
+            },
+            Err(err) => {
+                done = true;
+                Some(Err(err.into()))
+            },
+        }
+    }))
+}
 
-    // Recurse on sources
-    for srcroot in sources {
-        for entry in WalkDir::new(&srcroot).follow_links(false).sort_by(|x, y| x.file_name().cmp(y.file_name())) {
-            let srcpath = entry?.into_path();
-
-            // Set proper dst path
-            let dstpath = match clone {
-                true => dstabs.mash(srcpath.trim_prefix(&srcroot)),
-                false => dstabs.mash(srcpath.trim_prefix(srcroot.dir()?)),
-            };
-            match &srcpath {
-                // Copy dir links needs to be first as is_dir follows links
-                x if x.is_symlink_dir() => {
-                    symlink(&dstpath, srcpath.readlink()?)?;
-                },
-
-                // Create destination directories as needed
-                x if x.is_dir() => {
-                    mkdir(dstpath)?;
-                },
+/// Copier provides flexible options for recursively copying files and directories.
+pub struct Copier<'a> {
+    src: PathBuf,                                  // source path or glob
+    dst: PathBuf,                                  // destination path
+    atomic: bool,                                  // copy files atomically
+    deref_dst: bool,                               // follow a destination link to a directory
+    filter: Option<Regex>,                         // only copy files whose path matches
+    follow_links: bool,                            // follow links rather than recreate them
+    ignore: Option<PathBuf>,                       // .gitignore style file of paths to exclude
+    mode: Option<u32>,                             // mode to chmod copied files to if set
+    owner: bool,                                   // preserve the source ownership
+    progress: Option<Box<dyn FnMut(u64, u64)+'a>>, // progress callback
+    strict: bool,                                  // error rather than skip when ownership can't be preserved
+}
 
-                // Copy file
-                _ => {
-                    copyfile(&srcpath, &dstpath)?;
-                },
-            }
-        }
+impl<'a> Copier<'a> {
+    /// Update the `atomic` option. Default is disabled.
+    /// When `yes` is `true`, each file is copied to a temp name in the destination directory then
+    /// renamed into place so readers never see a partially written file.
+    pub fn atomic(&mut self, yes: bool) -> &mut Self {
+        self.atomic = yes;
+        self
     }
 
-    Ok(dstabs)
-}
+    /// Update the `deref_dst` option. Default is enabled.
+    /// When `yes` is `true`, a destination that is a link to a directory is followed and the
+    /// sources are copied into the link's target. When `false`, the link itself is treated as
+    /// the destination name and replaced by the copy leaving the link's target untouched.
+    pub fn deref_dst(&mut self, yes: bool) -> &mut Self {
+        self.deref_dst = yes;
+        self
+    }
 
-/// Copyfile provides a flexible options for copying files
-#[derive(Debug)]
-pub struct Copyfile {
-    src: PathBuf,       // source file
-    dst: PathBuf,       // destination path
-    mode: Option<u32>,  // mode to chmod the file to if set
-    follow_links: bool, // follow links when copying files
-}
+    /// Update the `filter` option. Default is disabled.
+    /// Only files and links whose absolute source path matches the given regex are copied.
+    /// Directories are always walked but only created as needed to hold copied files.
+    pub fn filter(&mut self, rx: Regex) -> &mut Self {
+        self.filter = Some(rx);
+        self
+    }
 
-impl Copyfile {
-    /// Update the `follow` option. Default is disabled.
-    /// When `yes` is `true`, links are followed.
-    pub fn follow(&mut self, yes: bool) -> &mut Self {
+    /// Update the `follow_links` option. Default is disabled.
+    /// When `yes` is `true`, links are followed and their targets copied rather than the links
+    /// being recreated at the destination.
+    pub fn follow_links(&mut self, yes: bool) -> &mut Self {
         self.follow_links = yes;
         self
     }
 
+    /// Update the `ignore_file` option. Default is disabled.
+    /// Paths matching the rules of the given `.gitignore` style file are excluded from the copy
+    /// and ignored directories are pruned entirely. Patterns are matched relative to the source
+    /// root and support `*`, `**`, `?`, negation `!` and directory only `dir/` forms.
+    pub fn ignore_file<T: AsRef<Path>>(&mut self, path: T) -> &mut Self {
+        self.ignore = Some(path.as_ref().to_path_buf());
+        self
+    }
+
     /// Update the `mode` option. Default is disabled.
+    /// When set, all copied files are changed to the given mode rather than the source mode.
+    /// Directories and links are unaffected.
     pub fn mode(&mut self, mode: u32) -> &mut Self {
         self.mode = Some(mode);
         self
     }
 
-    /// Execute the copyfile operation with the current options.
+    /// Update the `preserve_owner` option. Default is disabled.
+    /// When `yes` is `true`, the source uid/gid is applied to each copied entry without following
+    /// links. Ownership can only be preserved when running as root, otherwise it is silently
+    /// skipped unless the `strict` option is set.
+    pub fn preserve_owner(&mut self, yes: bool) -> &mut Self {
+        self.owner = yes;
+        self
+    }
+
+    /// Update the `progress` option. Default is disabled.
+    /// The given callback is called with the bytes copied so far and the total bytes to copy
+    /// after each file is copied. The total is calculated up front from the files selected for
+    /// copying so the final call always reports the total.
+    pub fn progress<F: FnMut(u64, u64)+'a>(&mut self, f: F) -> &mut Self {
+        self.progress = Some(Box::new(f));
+        self
+    }
+
+    /// Update the `strict` option. Default is disabled.
+    /// When `yes` is `true`, requesting `preserve_owner` while not running as root returns an
+    /// error rather than silently skipping the ownership change.
+    pub fn strict(&mut self, yes: bool) -> &mut Self {
+        self.strict = yes;
+        self
+    }
+
+    /// Execute the copy operation with the current options.
     pub fn copy(&mut self) -> FuResult<PathBuf> {
-        // Configure and check source
-        if !self.src.exists() {
+        let mut clone = true;
+
+        // Ownership can only be preserved as root
+        let owner = self.owner && user::is_root();
+        if self.owner && !owner && self.strict {
+            return Err(UserError::requires_root().into());
+        }
+
+        // Handle globbing
+        let sources = sys::glob(&self.src)?;
+        if sources.is_empty() {
             return Err(PathError::does_not_exist(&self.src).into());
         }
-        if self.src.is_dir() || self.src.is_symlink_dir() {
-            return Err(PathError::is_not_file_or_symlink_to_file(&self.src).into());
+
+        // Replace rather than follow a destination link if requested
+        if !self.deref_dst && self.dst.is_symlink() {
+            fs::remove_file(&self.dst)?;
         }
 
-        // Configure and check the destination
-        match self.dst.exists() {
-            // Exists so dst is either a file to overwrite or a dir to copy into
-            true => {
-                if self.dst.is_dir() {
-                    self.dst = self.dst.mash(self.src.base()?)
-                }
-            },
+        // Copy into destination vs clone as destination
+        if self.dst.is_dir() || sources.len() > 1 {
+            clone = false;
+        }
 
-            // Doesn't exist so dst is a new destination name, ensure all paths exist
-            false => {
-                let srcdir = self.src.dir()?;
-                let dstdir = self.dst.dir()?;
-                if srcdir != dstdir {
-                    mkdir(dstdir)?.chmod(srcdir.mode()?)?;
+        // Load ignore rules if given
+        let ignore = match &self.ignore {
+            Some(path) => IgnoreRules::load(path)?,
+            None => IgnoreRules::default(),
+        };
+
+        // Recurse on sources collecting the entries to copy
+        let mut entries = vec![];
+        for (i, srcroot) in sources.iter().enumerate() {
+            let walker = WalkDir::new(srcroot).follow_links(self.follow_links).sort_by(|x, y| x.file_name().cmp(y.file_name()));
+            for entry in walker.into_iter().filter_entry(|x| match x.path().strip_prefix(srcroot) {
+                Ok(rel) => !ignore.is_ignored(rel, x.file_type().is_dir()),
+                Err(_) => true,
+            }) {
+                let entry = entry?;
+
+                // Skip files and links that don't match the filter
+                if let Some(rx) = &self.filter {
+                    if !entry.file_type().is_dir() && !rx.is_match(&entry.path().to_string_lossy()) {
+                        continue;
+                    }
                 }
-            },
+                entries.push((i, entry));
+            }
         }
 
-        // Check for same file
-        if self.src == self.dst {
-            return Ok(self.dst.clone());
+        // Calculate the total bytes to copy up front
+        let mut total = 0;
+        if self.progress.is_some() {
+            for (_, entry) in entries.iter().filter(|(_, x)| x.file_type().is_file()) {
+                total += entry.metadata()?.len();
+            }
         }
 
-        // Recreate link or copy file including permissions
-        if self.src.is_symlink() {
-            symlink(&self.dst, self.src.readlink()?)?;
-        } else {
-            fs::copy(&self.src, &self.dst)?;
-            if let Some(mode) = self.mode {
-                chmod_p(&self.dst)?.mode(mode).recurse(false).chmod()?;
+        let mut copied = 0;
+        for (i, entry) in entries {
+            let srcroot = &sources[i];
+            let srcpath = entry.path().to_path_buf();
+
+            // Set proper dst path
+            let dstpath = match clone {
+                true => self.dst.mash(srcpath.trim_prefix(srcroot)),
+                false => self.dst.mash(srcpath.trim_prefix(srcroot.dir()?)),
+            };
+            match &srcpath {
+                // Copy dir links needs to be first as is_dir follows links
+                x if !self.follow_links && x.is_symlink_dir() => {
+                    symlink(&dstpath, srcpath.readlink()?)?;
+                },
+
+                // Create destination directories as needed
+                x if x.is_dir() => {
+                    if self.filter.is_some() {
+                        continue;
+                    }
+                    mkdir(&dstpath)?;
+                },
+
+                // Copy file
+                _ => {
+                    let mut copyfile = copyfile_p(&srcpath, &dstpath)?;
+                    copyfile.atomic(self.atomic).follow(self.follow_links);
+                    if let Some(mode) = self.mode {
+                        copyfile.mode(mode);
+                    }
+                    copyfile.copy()?;
+                    if self.progress.is_some() && entry.file_type().is_file() {
+                        copied += entry.metadata()?.len();
+                        self.report(copied, total);
+                    }
+                },
+            }
+
+            // Preserve ownership without following links
+            if owner {
+                let meta = match self.follow_links {
+                    true => fs::metadata(&srcpath)?,
+                    false => fs::symlink_metadata(&srcpath)?,
+                };
+                chown_one(&dstpath, meta.uid(), meta.gid(), false)?;
             }
         }
 
         Ok(self.dst.clone())
     }
-}
 
-/// Wraps `copyfile_p` to copy the given `src` to the given `dst`. Disables follow_links
+    // Report progress to the callback if set
+    fn report(&mut self, copied: u64, total: u64) {
+        if let Some(f) = self.progress.as_mut() {
+            f(copied, total);
+        }
+    }
+}
+
+/// Wraps `copy_p` to copy src to dst recursively creating destination directories as needed and
+/// handling path expansion and globbing e.g. copy("./*", "../") and returning an absolute path of
+/// the destination.
+///
+/// The dst will be copied to if it is an existing directory.
+/// The dst will be a clone of the src if it doesn't exist.
+/// Doesn't follow links
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_copy");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// let file2 = tmpdir.mash("file2");
+/// assert!(sys::touch(&file1).is_ok());
+/// assert!(sys::copy(&file1, &file2).is_ok());
+/// assert_eq!(file2.exists(), true);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn copy<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<PathBuf> {
+    copy_p(src, dst)?.copy()
+}
+
+/// Create [`Copier`] options providing path expansion, globbing and recursion while copying src
+/// to dst. This function provides more control over options than the `copy` function. Changes
+/// are not invoked until the `copy` method is called.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_copy_p");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// let file2 = tmpdir.mash("file2");
+/// assert!(sys::write(&file1, "this is a test").is_ok());
+/// assert!(sys::copy_p(&file1, &file2).unwrap().atomic(true).copy().is_ok());
+/// assert_eq!(sys::readstring(&file2).unwrap(), "this is a test");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn copy_p<'a, T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<Copier<'a>> {
+    Ok(Copier {
+        src: src.as_ref().to_path_buf(),
+        dst: dst.as_ref().abs()?,
+        atomic: false,
+        deref_dst: true,
+        filter: None,
+        follow_links: false,
+        ignore: None,
+        mode: None,
+        owner: false,
+        progress: None,
+        strict: false,
+    })
+}
+
+// Private `.gitignore` style rules used to exclude paths during a copy
+#[derive(Debug, Default)]
+struct IgnoreRules {
+    rules: Vec<(Regex, bool, bool)>, // (pattern, negated, dir only)
+}
+
+impl IgnoreRules {
+    // Parse the rules from the given ignore file
+    fn load<T: AsRef<Path>>(path: T) -> FuResult<IgnoreRules> {
+        let mut rules = Vec::new();
+        for line in readlines(path)? {
+            let mut pattern = line.trim_end();
+            if pattern.is_empty() || pattern.starts_with('#') {
+                continue;
+            }
+
+            // Negation and directory only forms
+            let negated = pattern.starts_with('!');
+            if negated {
+                pattern = &pattern[1..];
+            }
+            let dir_only = pattern.ends_with('/');
+            pattern = pattern.trim_end_matches('/');
+
+            // Patterns containing a slash are anchored to the root else match at any depth
+            let anchored = pattern.contains('/');
+            pattern = pattern.trim_start_matches('/');
+            if pattern.is_empty() {
+                continue;
+            }
+
+            let mut rx = String::from(if anchored { "^" } else { "^(?:.*/)?" });
+            let mut chars = pattern.chars().peekable();
+            while let Some(c) = chars.next() {
+                match c {
+                    '*' if chars.peek() == Some(&'*') => {
+                        chars.next();
+                        if chars.peek() == Some(&'/') {
+                            chars.next();
+                            rx.push_str("(?:.*/)?");
+                        } else {
+                            rx.push_str(".*");
+                        }
+                    },
+                    '*' => rx.push_str("[^/]*"),
+                    '?' => rx.push_str("[^/]"),
+                    _ => rx.push_str(&regex::escape(&c.to_string())),
+                }
+            }
+            rx.push('$');
+            rules.push((Regex::new(&rx)?, negated, dir_only));
+        }
+        Ok(IgnoreRules { rules })
+    }
+
+    // Returns true if the given relative path is ignored, last matching rule wins
+    fn is_ignored(&self, rel: &Path, is_dir: bool) -> bool {
+        let rel = match rel.to_str() {
+            Some(x) if !x.is_empty() => x,
+            _ => return false,
+        };
+        let mut ignored = false;
+        for (rx, negated, dir_only) in &self.rules {
+            if (!dir_only || is_dir) && rx.is_match(rel) {
+                ignored = !negated;
+            }
+        }
+        ignored
+    }
+}
+
+/// Copyfile provides a flexible options for copying files
+#[derive(Debug)]
+pub struct Copyfile {
+    src: PathBuf,       // source file
+    dst: PathBuf,       // destination path
+    mode: Option<u32>,  // mode to chmod the file to if set
+    follow_links: bool, // follow links when copying files
+    atomic: bool,       // copy to a temp file then rename into place
+    times: bool,        // preserve access and modification times
+}
+
+impl Copyfile {
+    /// Update the `atomic` option. Default is disabled.
+    /// When `yes` is `true`, the file is copied to a temp name in the destination directory then
+    /// renamed into place so readers never see a partially written file.
+    pub fn atomic(&mut self, yes: bool) -> &mut Self {
+        self.atomic = yes;
+        self
+    }
+
+    /// Update the `follow` option. Default is disabled.
+    /// When `yes` is `true`, links are followed.
+    pub fn follow(&mut self, yes: bool) -> &mut Self {
+        self.follow_links = yes;
+        self
+    }
+
+    /// Update the `mode` option. Default is disabled.
+    pub fn mode(&mut self, mode: u32) -> &mut Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Update the `preserve_times` option. Default is disabled.
+    /// When `yes` is `true`, the source file's access and modification times are applied to the
+    /// destination after copying.
+    pub fn preserve_times(&mut self, yes: bool) -> &mut Self {
+        self.times = yes;
+        self
+    }
+
+    /// Execute the copyfile operation with the current options.
+    pub fn copy(&mut self) -> FuResult<PathBuf> {
+        // Configure and check source
+        if !self.src.exists() {
+            return Err(PathError::does_not_exist(&self.src).into());
+        }
+        if self.src.is_dir() || self.src.is_symlink_dir() {
+            return Err(PathError::is_not_file_or_symlink_to_file(&self.src).into());
+        }
+
+        // Configure and check the destination
+        match self.dst.exists() {
+            // Exists so dst is either a file to overwrite or a dir to copy into
+            true => {
+                if self.dst.is_dir() {
+                    self.dst = self.dst.mash(self.src.base()?)
+                }
+            },
+
+            // Doesn't exist so dst is a new destination name, ensure all paths exist
+            false => {
+                let srcdir = self.src.dir()?;
+                let dstdir = self.dst.dir()?;
+                if srcdir != dstdir {
+                    // Only the directory itself, recursing would clobber the modes of its contents
+                    chmod_p(mkdir(dstdir)?)?.mode(srcdir.mode()?).recurse(false).chmod()?;
+                }
+            },
+        }
+
+        // Check for same file
+        if self.src == self.dst {
+            return Ok(self.dst.clone());
+        }
+
+        // Recreate link or copy file including permissions
+        if !self.follow_links && self.src.is_symlink() {
+            symlink(&self.dst, self.src.readlink()?)?;
+        } else if self.atomic {
+            let suffix: String = iter::repeat_with(fastrand::alphanumeric).take(8).collect();
+            let tmp = self.dst.dir()?.mash(format!(".{}.{}", self.dst.base()?, suffix));
+            let result = || -> FuResult<()> {
+                fs::copy(&self.src, &tmp)?;
+                if let Some(mode) = self.mode {
+                    chmod_p(&tmp)?.mode(mode).recurse(false).chmod()?;
+                }
+                if self.times {
+                    copy_times(&self.src, &tmp)?;
+                }
+                fs::rename(&tmp, &self.dst)?;
+                Ok(())
+            }();
+            if result.is_err() {
+                let _ = fs::remove_file(&tmp);
+            }
+            result?;
+        } else {
+            fs::copy(&self.src, &self.dst)?;
+            if let Some(mode) = self.mode {
+                chmod_p(&self.dst)?.mode(mode).recurse(false).chmod()?;
+            }
+            if self.times {
+                copy_times(&self.src, &self.dst)?;
+            }
+        }
+
+        Ok(self.dst.clone())
+    }
+}
+
+/// Wraps `copyfile_p` to copy the given `src` to the given `dst`. Disables follow_links
 /// and uses the source mode rather than an optionally set one.
 ///
 /// ### Examples
@@ -460,6 +1070,8 @@ pub fn copyfile_p<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<Co
         dst: dst.as_ref().abs()?,
         mode: None,
         follow_links: false,
+        atomic: false,
+        times: false,
     })
 }
 
@@ -480,7 +1092,109 @@ pub fn copyfile_p<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<Co
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// ```
 pub fn digest<T: AsRef<Path>>(path: T) -> FuResult<Vec<u8>> {
-    Ok(Blake2b::digest(&readbytes(path)?).into_iter().collect())
+    digest_stream(path)
+}
+
+/// Computes and returns a single combined digest of the given directory tree. Entries are walked
+/// in sorted order so identical trees always produce identical digests regardless of filesystem
+/// iteration order. Each entry's relative path is hashed along with a file's contents or a
+/// link's target path, links are never followed. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_digest_all");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let dir1 = tmpdir.mash("dir1");
+/// let dir2 = tmpdir.mash("dir2");
+/// assert!(sys::mkdir(&dir1).is_ok());
+/// assert!(sys::write(dir1.mash("file1"), "this is a test").is_ok());
+/// assert!(sys::copy(&dir1, &dir2).is_ok());
+/// assert_eq!(sys::digest_all(&dir1).unwrap(), sys::digest_all(&dir2).unwrap());
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn digest_all<T: AsRef<Path>>(path: T) -> FuResult<Vec<u8>> {
+    let path = path.as_ref().abs()?;
+    if !path.is_dir() {
+        return Err(PathError::is_not_dir(&path).into());
+    }
+
+    // Length prefix each field so that different trees can't produce the same byte stream
+    let mut hasher = Blake2b::new();
+    let mut update = |tag: u8, data: &[u8]| {
+        hasher.update([tag]);
+        hasher.update((data.len() as u64).to_le_bytes());
+        hasher.update(data);
+    };
+    for entry in WalkDir::new(&path).min_depth(1).follow_links(false).sort_by(|x, y| x.file_name().cmp(y.file_name())) {
+        let entry = entry?;
+        update(b'p', entry.path().trim_prefix(&path).as_os_str().as_bytes());
+        if entry.file_type().is_symlink() {
+            update(b'l', fs::read_link(entry.path())?.as_os_str().as_bytes());
+        } else if entry.file_type().is_file() {
+            update(b'f', &readbytes(entry.path())?);
+        }
+    }
+    Ok(hasher.finalize().into_iter().collect())
+}
+
+/// Computes and returns the digest of the given `path` reading the file in 64 KiB chunks rather
+/// than loading it into memory all at once. Suitable for very large files. Handles path
+/// expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_digest_stream");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::write(&file1, "this is a test").is_ok());
+/// assert_eq!(sys::digest_stream(&file1).unwrap(), sys::digest(&file1).unwrap());
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn digest_stream<T: AsRef<Path>>(path: T) -> FuResult<Vec<u8>> {
+    let mut reader = BufReader::new(File::open(path.as_ref().abs()?)?);
+    let mut hasher = Blake2b::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => hasher.update(&buffer[..n]),
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Ok(hasher.finalize().into_iter().collect())
+}
+
+/// Removes all the children of the given directory `path` including files, links and
+/// subdirectories recursively while leaving the directory itself in place. Handles path
+/// expansion and doesn't follow links.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_empty_dir");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(tmpdir.mash("dir1")).is_ok());
+/// assert!(sys::touch(tmpdir.mash("file1")).is_ok());
+/// assert!(sys::empty_dir(&tmpdir).is_ok());
+/// assert!(tmpdir.is_dir());
+/// assert!(sys::paths(&tmpdir).unwrap().is_empty());
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn empty_dir<T: AsRef<Path>>(path: T) -> FuResult<()> {
+    for child in sys::paths(path)? {
+        match fs::symlink_metadata(&child)?.is_dir() {
+            true => fs::remove_dir_all(&child)?,
+            false => fs::remove_file(&child)?,
+        }
+    }
+    Ok(())
 }
 
 /// Returns the first captured string from the given regular expression `rx`.
@@ -521,7 +1235,8 @@ pub fn extract_string<T: AsRef<Path>>(path: T, rx: &Regex) -> FuResult<String> {
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// ```
 pub fn extract_string_p<T: AsRef<Path>, U: AsRef<str>>(path: T, rx: U) -> FuResult<String> {
-    extract_string(path, &Regex::new(rx.as_ref())?)
+    let rx = rx.as_ref();
+    extract_string(path, &Regex::new(rx).map_err(|e| FileError::invalid_pattern(rx, e))?)
 }
 
 /// Returns the captured strings from the given regular expression `rx`.
@@ -565,101 +1280,535 @@ pub fn extract_strings<T: AsRef<Path>>(path: T, rx: &Regex) -> FuResult<Vec<Stri
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// ```
 pub fn extract_strings_p<T: AsRef<Path>, U: AsRef<str>>(path: T, rx: U) -> FuResult<Vec<String>> {
-    extract_strings(path, &Regex::new(rx.as_ref())?)
+    let rx = rx.as_ref();
+    extract_strings(path, &Regex::new(rx).map_err(|e| FileError::invalid_pattern(rx, e))?)
 }
 
-/// Creates the given directory and any parent directories needed, handling path expansion and
-/// returning an absolute path created.
+/// FileType identifies the type of a file's contents as detected by `filetype`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum FileType {
+    Elf,
+    Gzip,
+    Tar,
+    Text,
+    Unknown,
+    Zip,
+}
+
+impl fmt::Display for FileType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FileType::Elf => write!(f, "elf"),
+            FileType::Gzip => write!(f, "gzip"),
+            FileType::Tar => write!(f, "tar"),
+            FileType::Text => write!(f, "text"),
+            FileType::Unknown => write!(f, "unknown"),
+            FileType::Zip => write!(f, "zip"),
+        }
+    }
+}
+
+/// Returns the [`FileType`] of the given `path` by matching the magic signatures at the start of
+/// the file. Files are considered `Text` if the first kilobyte is valid UTF-8 without any NUL
+/// bytes. Handles path expansion.
 ///
 /// ### Examples
 /// ```
 /// use fungus::prelude::*;
 ///
-/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_mkdir");
+/// assert_eq!(sys::filetype("tests/alpine-base.tgz").unwrap(), sys::FileType::Gzip);
+/// assert_eq!(sys::filetype("tests/alpine-base.tar").unwrap(), sys::FileType::Tar);
+/// ```
+pub fn filetype<T: AsRef<Path>>(path: T) -> FuResult<FileType> {
+    let data = readchunk(path, 0, 1024)?;
+    if data.starts_with(&[0x1f, 0x8b]) {
+        return Ok(FileType::Gzip);
+    } else if data.starts_with(&[0x7f, b'E', b'L', b'F']) {
+        return Ok(FileType::Elf);
+    } else if data.starts_with(b"PK\x03\x04") {
+        return Ok(FileType::Zip);
+    } else if data.len() >= 262 && &data[257..262] == b"ustar" {
+        return Ok(FileType::Tar);
+    } else if !data.contains(&0) {
+        // Allow for a multi-byte character cut off at the end of the sample
+        match str::from_utf8(&data) {
+            Ok(_) => return Ok(FileType::Text),
+            Err(err) if err.error_len().is_none() => return Ok(FileType::Text),
+            Err(_) => (),
+        }
+    }
+    Ok(FileType::Unknown)
+}
+
+/// Streams the lines of the given file through the callback `f` one at a time without buffering
+/// the whole file, stopping on and returning the first error the callback returns. Handles path
+/// expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_for_each_line");
 /// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
 /// assert!(sys::mkdir(&tmpdir).is_ok());
-/// assert_eq!(tmpdir.exists(), true);
+/// assert!(sys::write(&tmpfile, "one\ntwo").is_ok());
+/// let mut count = 0;
+/// assert!(sys::for_each_line(&tmpfile, |_| {
+///     count += 1;
+///     Ok(())
+/// }).is_ok());
+/// assert_eq!(count, 2);
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// ```
-pub fn mkdir<T: AsRef<Path>>(path: T) -> FuResult<PathBuf> {
-    let path = path.as_ref().abs()?;
-    if !path.exists() {
-        fs::create_dir_all(&path)?;
+pub fn for_each_line<T: AsRef<Path>, F>(path: T, mut f: F) -> FuResult<()>
+where
+    F: FnMut(&str) -> FuResult<()>,
+{
+    for line in readlines_p(path)? {
+        f(&line?)?;
     }
-    Ok(path)
+    Ok(())
 }
 
-/// Wraps `mkdir` allowing for setting the directory's mode.
+/// Returns the 1-based line number and line for every line in the file matching the given regular
+/// expression `rx`. The file is streamed rather than loaded into memory. Handles path expansion.
 ///
 /// ### Examples
 /// ```
 /// use fungus::prelude::*;
 ///
-/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_mkdir");
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_grep");
 /// assert!(sys::remove_all(&tmpdir).is_ok());
-/// assert!(sys::mkdir_p(&tmpdir, 0o555).is_ok());
-/// assert_eq!(tmpdir.mode().unwrap(), 0o40555);
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::write(&tmpfile, "foo\nbar\nfoobar").is_ok());
+/// let rx = Regex::new(r"^foo").unwrap();
+/// assert_eq!(sys::grep(&tmpfile, &rx).unwrap(), vec![(1, "foo".to_string()), (3, "foobar".to_string())]);
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// ```
-pub fn mkdir_p<T: AsRef<Path>>(path: T, mode: u32) -> FuResult<PathBuf> {
-    let path = mkdir(path)?;
-    chmod_p(&path)?.recurse(false).mode(mode).chmod()?;
-    Ok(path)
+pub fn grep<T: AsRef<Path>>(path: T, rx: &Regex) -> FuResult<Vec<(usize, String)>> {
+    let mut lines = vec![];
+    for (i, line) in readlines_p(path)?.enumerate() {
+        let line = line?;
+        if rx.is_match(&line) {
+            lines.push((i + 1, line));
+        }
+    }
+    Ok(lines)
 }
 
-/// Move a file or directory handling path expansion and globbing. Replaces destination files if
-/// exist but always moves `src` into `dst` if `dst` is an existing directory.
+/// Returns the 1-based line number and line for every line in the file matching the given regular
+/// expression `rx`.
 ///
 /// ### Examples
 /// ```
 /// use fungus::prelude::*;
 ///
-/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_copy");
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_grep_p");
 /// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
 /// assert!(sys::mkdir(&tmpdir).is_ok());
-/// let file1 = tmpdir.mash("file1");
-/// let file2 = tmpdir.mash("file2");
-/// assert!(sys::touch(&file1).is_ok());
-/// assert!(sys::move_p(&file1, &file2).is_ok());
-/// assert_eq!(file1.exists(), false);
-/// assert_eq!(file2.exists(), true);
+/// assert!(sys::write(&tmpfile, "foo\nbar\nfoobar").is_ok());
+/// assert_eq!(sys::grep_p(&tmpfile, r"bar$").unwrap(), vec![(2, "bar".to_string()), (3, "foobar".to_string())]);
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// ```
-pub fn move_p<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<()> {
-    let src = src.as_ref().abs()?;
-
-    // Handle globbing
-    let sources = sys::glob(&src)?;
-    if sources.is_empty() {
-        return Err(PathError::does_not_exist(&src).into());
-    }
-
-    // Test if dst exists and is a directory
-    let dst = dst.as_ref().abs()?;
-    let dst_is_dir = dst.is_dir();
-
-    // Execute the move for all sources
-    for source in sources {
-        let dstpath = match dst_is_dir {
-            true => dst.mash(src.base()?),
-            false => dst.clone(),
-        };
-        fs::rename(source, dstpath)?;
-    }
-    Ok(())
+pub fn grep_p<T: AsRef<Path>, U: AsRef<str>>(path: T, rx: U) -> FuResult<Vec<(usize, String)>> {
+    let rx = rx.as_ref();
+    grep(path, &Regex::new(rx).map_err(|e| FileError::invalid_pattern(rx, e))?)
 }
 
-/// Removes the given empty directory or file. Handles path expansion. Does
-/// not follow symbolic links but rather removes the links themselves.
+/// Returns the first `n` lines of the given file or fewer if the file is shorter. Reading stops as
+/// soon as `n` lines have been read. Handles path expansion.
 ///
 /// ### Examples
 /// ```
 /// use fungus::prelude::*;
 ///
-/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_remove");
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_head");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
 /// assert!(sys::mkdir(&tmpdir).is_ok());
-/// assert!(sys::remove(&tmpdir).is_ok());
-/// assert_eq!(tmpdir.exists(), false);
+/// assert!(sys::write(&tmpfile, "one\ntwo\nthree").is_ok());
+/// assert_iter_eq(sys::head(&tmpfile, 2).unwrap(), vec!["one", "two"]);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn head<T: AsRef<Path>>(path: T, n: usize) -> FuResult<Vec<String>> {
+    Ok(readlines_p(path)?.take(n).collect::<io::Result<Vec<String>>>()?)
+}
+
+/// Returns the number of lines in the given file by streaming it rather than loading it into
+/// memory. A final line without a trailing newline is still counted. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_line_count");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::write(&tmpfile, "one\ntwo").is_ok());
+/// assert_eq!(sys::line_count(&tmpfile).unwrap(), 2);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn line_count<T: AsRef<Path>>(path: T) -> FuResult<usize> {
+    let path = path.as_ref().abs()?;
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut count = 0;
+    let mut last = b'\n';
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        let len = buf.len();
+        count += buf.iter().filter(|x| **x == b'\n').count();
+        last = buf[len - 1];
+        reader.consume(len);
+    }
+    if last != b'\n' {
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Creates the given directory and any parent directories needed, handling path expansion and
+/// returning an absolute path created.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_mkdir");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert_eq!(tmpdir.exists(), true);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn mkdir<T: AsRef<Path>>(path: T) -> FuResult<PathBuf> {
+    let path = path.as_ref().abs()?;
+    if !path.exists() {
+        fs::create_dir_all(&path)?;
+    }
+    Ok(path)
+}
+
+/// Creates the given directory and any parent directories needed applying `mode` to every
+/// directory actually created. Existing directories are left untouched. Handles
+/// path expansion and returns the absolute path created.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_mkdir_m");
+/// let dir1 = tmpdir.mash("dir1");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir_m(&dir1, 0o700).is_ok());
+/// assert_eq!(tmpdir.mode().unwrap(), 0o40700);
+/// assert_eq!(dir1.mode().unwrap(), 0o40700);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn mkdir_m<T: AsRef<Path>>(path: T, mode: u32) -> FuResult<PathBuf> {
+    let path = path.as_ref().abs()?;
+    let missing: Vec<&Path> = path.ancestors().take_while(|x| !x.exists()).collect();
+
+    // Create all missing directories with owner access first so a restrictive mode can't block
+    // creating their children then apply the mode from the deepest up
+    for dir in missing.iter().rev() {
+        fs::DirBuilder::new().mode(mode | 0o700).create(dir)?;
+    }
+    for dir in missing {
+        fs::set_permissions(dir, fs::Permissions::from_mode(mode))?;
+    }
+    Ok(path)
+}
+
+/// Wraps `mkdir` allowing for setting the directory's mode.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_mkdir");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir_p(&tmpdir, 0o555).is_ok());
+/// assert_eq!(tmpdir.mode().unwrap(), 0o40555);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn mkdir_p<T: AsRef<Path>>(path: T, mode: u32) -> FuResult<PathBuf> {
+    let path = mkdir(path)?;
+    chmod_p(&path)?.recurse(false).mode(mode).chmod()?;
+    Ok(path)
+}
+
+/// Move a file or directory handling path expansion and globbing. Replaces destination files if
+/// exist but always moves `src` into `dst` if `dst` is an existing directory. Moves across
+/// devices fall back to copying then removing the source preserving modes and links.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_copy");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// let file2 = tmpdir.mash("file2");
+/// assert!(sys::touch(&file1).is_ok());
+/// assert!(sys::move_p(&file1, &file2).is_ok());
+/// assert_eq!(file1.exists(), false);
+/// assert_eq!(file2.exists(), true);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn move_p<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<()> {
+    mover(src, dst)?.mv()
+}
+
+// Private helper to copy the source to the destination preserving modes and links then remove
+// the source. The callback `f` is called with the size of each file after it is copied.
+fn move_across<F: FnMut(u64)>(src: &Path, dst: &Path, mut f: F) -> FuResult<()> {
+    for entry in WalkDir::new(src).follow_links(false).sort_by(|x, y| x.file_name().cmp(y.file_name())) {
+        let entry = entry?;
+        let srcpath = entry.path();
+        let dstpath = dst.mash(srcpath.trim_prefix(src));
+        if entry.file_type().is_symlink() {
+            symlink(&dstpath, srcpath.readlink()?)?;
+        } else if entry.file_type().is_dir() {
+            chmod_p(mkdir(&dstpath)?)?.mode(srcpath.mode()?).recurse(false).chmod()?;
+        } else {
+            copyfile(srcpath, &dstpath)?;
+            f(entry.metadata()?.len());
+        }
+    }
+    match fs::symlink_metadata(src)?.is_dir() {
+        true => fs::remove_dir_all(src)?,
+        false => fs::remove_file(src)?,
+    }
+    Ok(())
+}
+
+/// Mover provides options for moving files and directories with optional progress reporting.
+/// Moves within a device are simple renames while moves across devices fall back to copying
+/// then removing the source.
+pub struct Mover<'a> {
+    src: PathBuf,                                  // source path or glob
+    dst: PathBuf,                                  // destination path
+    across: bool,                                  // always copy then remove as across devices
+    progress: Option<Box<dyn FnMut(u64, u64)+'a>>, // progress callback
+}
+
+impl<'a> Mover<'a> {
+    /// Update the `across` option. Default is false.
+    /// When true the move always copies then removes the source as it would across devices
+    /// rather than attempting a rename first.
+    pub fn across(&mut self, yes: bool) -> &mut Self {
+        self.across = yes;
+        self
+    }
+
+    /// Update the `progress` option. Default is disabled.
+    /// The given callback is called with the bytes moved so far and the total bytes to move.
+    /// Renames on the same device report completion immediately while moves across devices
+    /// report after each file is copied.
+    pub fn progress<F: FnMut(u64, u64)+'a>(&mut self, f: F) -> &mut Self {
+        self.progress = Some(Box::new(f));
+        self
+    }
+
+    /// Execute the move operation with the current options.
+    pub fn mv(&mut self) -> FuResult<()> {
+        // Handle globbing
+        let sources = sys::glob(&self.src)?;
+        if sources.is_empty() {
+            return Err(PathError::does_not_exist(&self.src).into());
+        }
+
+        // Calculate the total bytes to move up front only when reporting progress
+        let mut sizes = vec![0; sources.len()];
+        if self.progress.is_some() {
+            for (source, size) in sources.iter().zip(sizes.iter_mut()) {
+                // Links are moved as is so they count for nothing and may be dangling
+                if !source.is_symlink() {
+                    *size = sys::size(source)?;
+                }
+            }
+        }
+        let total = sizes.iter().sum();
+
+        // Execute the move for all sources
+        let mut moved = 0;
+        let dst_is_dir = self.dst.is_dir();
+        for (source, size) in sources.iter().zip(sizes) {
+            let dstpath = match dst_is_dir {
+                true => self.dst.mash(source.base()?),
+                false => self.dst.clone(),
+            };
+            let result = match self.across {
+                true => Err(io::Error::from_raw_os_error(libc::EXDEV)),
+                false => fs::rename(source, &dstpath),
+            };
+            match result {
+                Ok(_) => {
+                    moved += size;
+                    self.report(moved, total);
+                },
+                Err(ref err) if err.raw_os_error() == Some(libc::EXDEV) => {
+                    move_across(source, &dstpath, |size| {
+                        moved += size;
+                        self.report(moved, total);
+                    })?;
+                },
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(())
+    }
+
+    // Report progress to the callback if set
+    fn report(&mut self, moved: u64, total: u64) {
+        if let Some(f) = self.progress.as_mut() {
+            f(moved, total);
+        }
+    }
+}
+
+/// Create [`Mover`] options for moving `src` to `dst` handling path expansion and globbing. This
+/// function provides more control over options than the `move_p` function. Changes are not
+/// invoked until the `mv` method is called.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_mover");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// let file2 = tmpdir.mash("file2");
+/// assert!(sys::write(&file1, "this is a test").is_ok());
+/// let mut done = 0;
+/// assert!(sys::mover(&file1, &file2).unwrap().progress(|x, _| done = x).mv().is_ok());
+/// assert_eq!(done, 14);
+/// assert_eq!(file2.exists(), true);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn mover<'a, T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<Mover<'a>> {
+    Ok(Mover { src: src.as_ref().abs()?, dst: dst.as_ref().abs()?, across: false, progress: None })
+}
+
+/// Overlay the contents of `src` onto the existing `dst` directory, recursively merging
+/// directories, creating missing directories and overwriting conflicting files while preserving
+/// unrelated files already in `dst`. Unlike `copy`, `src` is never nested as a subdirectory of
+/// `dst`. Handles path expansion and doesn't follow links in `src`, while links to directories
+/// in `dst` are merged through. Fails if `dst` or any of its entries conflict in type with `src`
+/// rather than removing them.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_overlay");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let dir1 = tmpdir.mash("dir1");
+/// let dir2 = tmpdir.mash("dir2");
+/// assert!(sys::mkdir(&dir1).is_ok());
+/// assert!(sys::mkdir(&dir2).is_ok());
+/// assert!(sys::write(dir1.mash("file1"), "new").is_ok());
+/// assert!(sys::write(dir2.mash("file2"), "keep").is_ok());
+/// assert!(sys::overlay(&dir1, &dir2).is_ok());
+/// assert_eq!(sys::readstring(dir2.mash("file1")).unwrap(), "new");
+/// assert_eq!(sys::readstring(dir2.mash("file2")).unwrap(), "keep");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn overlay<T: AsRef<Path>, U: AsRef<Path>>(src: T, dst: U) -> FuResult<()> {
+    let src = src.as_ref().abs()?;
+    let dst = dst.as_ref().abs()?;
+    if !src.exists() {
+        return Err(PathError::does_not_exist(&src).into());
+    }
+    if !src.is_dir() {
+        return Err(PathError::is_not_dir(&src).into());
+    }
+
+    if dst.exists() && !dst.is_dir() {
+        return Err(PathError::is_not_dir(&dst).into());
+    }
+
+    for entry in WalkDir::new(&src).follow_links(false).sort_by(|x, y| x.file_name().cmp(y.file_name())) {
+        let entry = entry?;
+        let srcpath = entry.path();
+        let srctype = entry.file_type();
+        let dstpath = dst.mash(srcpath.trim_prefix(&src));
+
+        // Merge into existing directories, including links to directories, or create them
+        if srctype.is_dir() {
+            if !dstpath.is_dir() {
+                if fs::symlink_metadata(&dstpath).is_ok() {
+                    return Err(PathError::is_not_dir(&dstpath).into());
+                }
+                chmod_p(mkdir(&dstpath)?)?.mode(srcpath.mode()?).recurse(false).chmod()?;
+            }
+            continue;
+        }
+
+        // Files and links replace existing files and links but never directories
+        if dstpath.is_dir() {
+            return Err(PathError::is_not_file(&dstpath).into());
+        }
+        if let Ok(meta) = fs::symlink_metadata(&dstpath) {
+            if meta.file_type().is_symlink() || srctype.is_symlink() {
+                fs::remove_file(&dstpath)?;
+            }
+        }
+        if srctype.is_symlink() {
+            symlink(&dstpath, srcpath.readlink()?)?;
+        } else {
+            copyfile(srcpath, &dstpath)?;
+        }
+    }
+    Ok(())
+}
+
+/// Insert `[u8]` data at the start of a file which means `str` or `String`, creating the file if
+/// it doesn't exist. The new content is written to a temp file in the same directory which is then
+/// renamed over the original preserving its mode, so a failure part way through never leaves a
+/// corrupted file behind. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_prepend");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::write(&tmpfile, "bar").is_ok());
+/// assert!(sys::prepend(&tmpfile, "foo").is_ok());
+/// assert_eq!(sys::readstring(&tmpfile).unwrap(), "foobar");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn prepend<T: AsRef<Path>, U: AsRef<[u8]>>(path: T, data: U) -> FuResult<()> {
+    let path = path.as_ref().abs()?;
+    let mut bytes = data.as_ref().to_vec();
+    if path.exists() {
+        bytes.extend(readbytes(&path)?);
+    }
+    write_atomic(&path, bytes)
+}
+
+/// Removes the given empty directory or file. Handles path expansion. Does
+/// not follow symbolic links but rather removes the links themselves.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_remove");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::remove(&tmpdir).is_ok());
+/// assert_eq!(tmpdir.exists(), false);
 /// ```
 pub fn remove<T: AsRef<Path>>(path: T) -> FuResult<()> {
     let path = path.as_ref().abs()?;
@@ -693,6 +1842,33 @@ pub fn remove_all<T: AsRef<Path>>(path: T) -> FuResult<()> {
     Ok(())
 }
 
+/// Removes the regular files and links matching the given glob `pattern` leaving any matching
+/// directories in place. Handles path expansion and returns the paths actually removed. A pattern
+/// that doesn't match anything returns an empty vector.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_remove_files");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1.tmp");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::touch(&file1).is_ok());
+/// assert_eq!(sys::remove_files(tmpdir.mash("*.tmp")).unwrap(), vec![file1]);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn remove_files<T: AsRef<Path>>(pattern: T) -> FuResult<Vec<PathBuf>> {
+    let mut removed = Vec::new();
+    for path in sys::glob(pattern)? {
+        if !fs::symlink_metadata(&path)?.is_dir() {
+            fs::remove_file(&path)?;
+            removed.push(path);
+        }
+    }
+    Ok(removed)
+}
+
 /// Returns the contents of the `path` as a `Vec<u8>`.
 ///
 /// ### Examples
@@ -715,6 +1891,30 @@ pub fn readbytes<T: AsRef<Path>>(path: T) -> FuResult<Vec<u8>> {
     }
 }
 
+/// Returns up to `len` bytes of the file `path` starting at the given `offset`. Fewer bytes are
+/// returned if the end of the file is reached first. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_readchunk");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::write(&tmpfile, "this is a test").is_ok());
+/// assert_eq!(sys::readchunk(&tmpfile, 5, 2).unwrap(), b"is".to_vec());
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn readchunk<T: AsRef<Path>>(path: T, offset: u64, len: usize) -> FuResult<Vec<u8>> {
+    let path = path.as_ref().abs()?;
+    let mut f = File::open(path)?;
+    f.seek(io::SeekFrom::Start(offset))?;
+    let mut data = Vec::with_capacity(len);
+    f.take(len as u64).read_to_end(&mut data)?;
+    Ok(data)
+}
+
 /// Returns all lines from teh file as a `Vec<String>`.
 ///
 /// ### Examples
@@ -817,8 +2017,38 @@ pub fn symlink<T: AsRef<Path>, U: AsRef<Path>>(link: T, target: U) -> FuResult<P
     Ok(path)
 }
 
+/// Returns the last `n` lines of the given file or fewer if the file is shorter. The file is
+/// streamed keeping at most `n` lines in memory. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_tail");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::write(&tmpfile, "one\ntwo\nthree").is_ok());
+/// assert_iter_eq(sys::tail(&tmpfile, 2).unwrap(), vec!["two", "three"]);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn tail<T: AsRef<Path>>(path: T, n: usize) -> FuResult<Vec<String>> {
+    let mut lines = VecDeque::with_capacity(n);
+    for line in readlines_p(path)? {
+        let line = line?;
+        if n > 0 {
+            if lines.len() == n {
+                lines.pop_front();
+            }
+            lines.push_back(line);
+        }
+    }
+    Ok(lines.into_iter().collect())
+}
+
 /// Create an empty file similar to the linux touch command. Handles path expansion.
 /// Uses default file creation permissions 0o666 - umask usually ends up being 0o644.
+/// Existing files are left as is, use `touch_m` to also update their times.
 ///
 /// ### Examples
 /// ```
@@ -840,27 +2070,60 @@ pub fn touch<T: AsRef<Path>>(path: T) -> FuResult<PathBuf> {
     Ok(path)
 }
 
-/// Wraps `touch` allowing for setting the file's mode.
+/// Create an empty file or update the access and modification times of an existing file to now,
+/// matching the behavior of the linux touch command. Unlike `touch` which leaves existing files
+/// untouched, `touch_m` always bumps the times. Handles path expansion and returns the absolute
+/// path.
 ///
 /// ### Examples
 /// ```
 /// use fungus::prelude::*;
 ///
-/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_touch");
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_touch_m");
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// let tmpfile = tmpdir.mash("file1");
 /// assert!(sys::mkdir(&tmpdir).is_ok());
-/// assert!(sys::touch_p(&tmpfile, 0o555).is_ok());
-/// assert_eq!(tmpfile.mode().unwrap(), 0o100555);
+/// assert!(sys::touch_m(&tmpfile).is_ok());
+/// let mtime = tmpfile.metadata().unwrap().modified().unwrap();
+/// assert!(sys::touch_m(&tmpfile).is_ok());
+/// assert!(tmpfile.metadata().unwrap().modified().unwrap() >= mtime);
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// ```
-pub fn touch_p<T: AsRef<Path>>(path: T, mode: u32) -> FuResult<PathBuf> {
-    let path = touch(path)?;
-    chmod_p(&path)?.recurse(false).mode(mode).chmod()?;
+pub fn touch_m<T: AsRef<Path>>(path: T) -> FuResult<PathBuf> {
+    let path = path.as_ref().abs()?;
+    if !path.exists() {
+        File::create(&path)?;
+    } else {
+        let times = [libc::timespec { tv_sec: 0, tv_nsec: libc::UTIME_NOW }, libc::timespec { tv_sec: 0, tv_nsec: libc::UTIME_NOW }];
+        let cpath = CString::new(path.as_os_str().as_bytes())?;
+        if unsafe { libc::utimensat(libc::AT_FDCWD, cpath.as_ptr(), times.as_ptr(), 0) } != 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+    }
     Ok(path)
 }
 
-/// Write `[u8]` data to a file which means `str` or `String`. Handles path expansion.
+/// Wraps `touch` allowing for setting the file's mode.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_touch");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::touch_p(&tmpfile, 0o555).is_ok());
+/// assert_eq!(tmpfile.mode().unwrap(), 0o100555);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn touch_p<T: AsRef<Path>>(path: T, mode: u32) -> FuResult<PathBuf> {
+    let path = touch(path)?;
+    chmod_p(&path)?.recurse(false).mode(mode).chmod()?;
+    Ok(path)
+}
+
+/// Write `[u8]` data to a file which means `str` or `String`. Handles path expansion.
 ///
 /// ### Examples
 /// ```
@@ -884,6 +2147,78 @@ pub fn write<T: AsRef<Path>, U: AsRef<[u8]>>(path: T, data: U) -> FuResult<()> {
     Ok(())
 }
 
+/// Write `[u8]` data to a file atomically by first writing to a temp file in the same directory,
+/// syncing it and then renaming it over the target. The target's existing mode is preserved. A
+/// crash part way through leaves either the old or the new content but never a partial file.
+/// Handles path expansion and follows links so the link's final target is replaced rather than
+/// the link itself.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_write_atomic");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::write_atomic(&tmpfile, "this is a test").is_ok());
+/// assert_eq!(sys::readstring(&tmpfile).unwrap(), "this is a test");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn write_atomic<T: AsRef<Path>, U: AsRef<[u8]>>(path: T, data: U) -> FuResult<()> {
+    let path = path.as_ref().resolve()?;
+
+    // Same directory placement keeps the rename on the same filesystem
+    let suffix: String = iter::repeat_with(fastrand::alphanumeric).take(8).collect();
+    let tmp = path.dir()?.mash(format!(".{}.{}", path.base()?, suffix));
+    let result = || -> FuResult<()> {
+        // Create the temp file with the target's mode so the data is never more exposed than it
+        // was, then set the mode exactly as the umask may have masked bits off
+        let perms = match path.exists() {
+            true => Some(path.metadata()?.permissions()),
+            false => None,
+        };
+        let mode = perms.as_ref().map(|x| x.mode() & 0o7777).unwrap_or(0o666);
+        let mut f = OpenOptions::new().write(true).create_new(true).mode(mode).open(&tmp)?;
+        if let Some(perms) = perms {
+            fs::set_permissions(&tmp, perms)?;
+        }
+        f.write_all(data.as_ref())?;
+        f.sync_all()?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// Write `[u8]` data to a file only if it differs from the file's current content, preserving
+/// the modification time of unchanged files. Missing files are always written. Returns `true`
+/// if the file was written. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("file_doc_write_if_changed");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert_eq!(sys::write_if_changed(&tmpfile, "this is a test").unwrap(), true);
+/// assert_eq!(sys::write_if_changed(&tmpfile, "this is a test").unwrap(), false);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn write_if_changed<T: AsRef<Path>, U: AsRef<[u8]>>(path: T, data: U) -> FuResult<bool> {
+    let path = path.as_ref().abs()?;
+    if path.is_file() && readbytes(&path)? == data.as_ref() {
+        return Ok(false);
+    }
+    write(&path, data)?;
+    Ok(true)
+}
+
 /// Wraps `write` allowing for setting the file's mode.
 ///
 /// ### Examples
@@ -951,7 +2286,9 @@ pub fn writelines_p<T: AsRef<Path>>(path: T, data: &[String], mode: u32) -> FuRe
 // -------------------------------------------------------------------------------------------------
 #[cfg(test)]
 mod tests {
+    use super::move_across;
     use crate::prelude::*;
+    use blake2::{Blake2b, Digest};
 
     // Test setup
     fn setup() -> PathBuf {
@@ -965,6 +2302,112 @@ mod tests {
         assert_eq!(format!("{}", FileError::FailedToExtractString), "failed to extract string from file");
     }
 
+    #[test]
+    fn test_append() {
+        let tmpdir = setup().mash("file_append");
+        let tmpfile = tmpdir.mash("file1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // creates the file then appends
+        assert!(sys::append(&tmpfile, "foo\n").is_ok());
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "foo\n");
+        assert!(sys::append(&tmpfile, "bar\n").is_ok());
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "foo\nbar\n");
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_append_p() {
+        let tmpdir = setup().mash("file_append_p");
+        let tmpfile = tmpdir.mash("file1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // mode is set on creation
+        assert!(sys::append_p(&tmpfile, "foo", 0o600).is_ok());
+        assert_eq!(tmpfile.mode().unwrap(), 0o100600);
+
+        // existing mode is kept
+        assert!(sys::append_p(&tmpfile, "bar", 0o644).is_ok());
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "foobar");
+        assert_eq!(tmpfile.mode().unwrap(), 0o100600);
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_appendlines() {
+        let tmpdir = setup().mash("file_appendlines");
+        let tmpfile = tmpdir.mash("file1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // append to lines without a trailing newline
+        assert!(sys::writelines(&tmpfile, &[String::from("one"), String::from("two")]).is_ok());
+        assert!(sys::appendlines(&tmpfile, &[String::from("three"), String::from("four")]).is_ok());
+        assert!(sys::appendlines(&tmpfile, &[String::from("five")]).is_ok());
+        assert_iter_eq(sys::readlines(&tmpfile).unwrap(), vec!["one", "two", "three", "four", "five"]);
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "one\ntwo\nthree\nfour\nfive\n");
+
+        // creates a missing file
+        let tmpfile = tmpdir.mash("file2");
+        assert!(sys::appendlines(&tmpfile, &[String::from("one")]).is_ok());
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "one\n");
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_backup() {
+        let tmpdir = setup().mash("file_backup");
+        let file1 = tmpdir.mash("file1");
+        let bak1 = tmpdir.mash("file1.bak");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::write_p(&file1, "first", 0o600).is_ok());
+
+        // first backup
+        assert_eq!(sys::backup(&file1).unwrap(), bak1);
+        assert_eq!(sys::readstring(&bak1).unwrap(), "first");
+        assert_eq!(bak1.mode().unwrap(), 0o100600);
+        let (src, dst) = (file1.metadata().unwrap(), bak1.metadata().unwrap());
+        assert_eq!((src.mtime(), src.mtime_nsec()), (dst.mtime(), dst.mtime_nsec()));
+
+        // second backup is timestamped and leaves the first alone
+        assert!(sys::write(&file1, "second").is_ok());
+        let bak2 = sys::backup(&file1).unwrap();
+        assert_ne!(bak2, bak1);
+        assert!(bak2.base().unwrap().starts_with("file1."));
+        assert!(bak2.base().unwrap().ends_with(".bak"));
+        assert_eq!(sys::readstring(&bak1).unwrap(), "first");
+        assert_eq!(sys::readstring(&bak2).unwrap(), "second");
+
+        // third backup doesn't clobber the second
+        let bak3 = sys::backup(&file1).unwrap();
+        assert_ne!(bak3, bak2);
+        assert_eq!(sys::files(&tmpdir).unwrap().len(), 4);
+
+        // errors
+        assert!(sys::backup(tmpdir.mash("foo")).is_err());
+        assert!(sys::backup(&tmpdir).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_chmod() {
         let tmpdir = setup().mash("file_chmod");
@@ -1036,6 +2479,65 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_chmod_p_best_effort() {
+        let tmpdir = setup().mash("file_chmod_p_best_effort");
+        let file1 = tmpdir.mash("file1");
+        let link1 = tmpdir.mash("dangling");
+        let file2 = tmpdir.mash("file2");
+
+        // setup, a dangling link can't have its target's mode changed
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::touch_p(&file1, 0o644).is_ok());
+        assert!(sys::touch_p(&file2, 0o644).is_ok());
+        assert!(sys::symlink(&link1, "foo").is_ok());
+
+        // fail fast by default
+        assert!(sys::chmod_p(&tmpdir).unwrap().files().mode(0o600).chmod().is_err());
+        assert_eq!(file1.mode().unwrap(), 0o100644);
+        assert_eq!(file2.mode().unwrap(), 0o100644);
+
+        // best effort continues and reports the failures
+        let err = sys::chmod_p(&tmpdir).unwrap().files().mode(0o640).best_effort(true).chmod().unwrap_err();
+        assert_eq!(err.downcast_ref::<FileError>(), Some(&FileError::failed_to_chmod(vec![&link1])));
+        assert_eq!(file1.mode().unwrap(), 0o100640);
+        assert_eq!(file2.mode().unwrap(), 0o100640);
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_chmod_p_only_scripts() {
+        let tmpdir = setup().mash("file_chmod_p_only_scripts");
+        let dir1 = tmpdir.mash("dir1");
+        let script1 = tmpdir.mash("script1");
+        let script2 = dir1.mash("script2");
+        let file1 = tmpdir.mash("file1");
+        let file2 = dir1.mash("file2");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::chmod_p(&dir1).unwrap().mode(0o750).recurse(false).chmod().is_ok());
+        assert!(sys::write_p(&script1, "#!/bin/bash\necho foo\n", 0o644).is_ok());
+        assert!(sys::write_p(&script2, "#!/usr/bin/env python\n", 0o600).is_ok());
+        assert!(sys::write_p(&file1, "plain text\n", 0o644).is_ok());
+        assert!(sys::write_p(&file2, "#", 0o644).is_ok());
+
+        // only scripts get the execute bit added to their existing mode
+        assert!(sys::chmod_p(&tmpdir).unwrap().only_scripts(true).chmod().is_ok());
+        assert_eq!(script1.mode().unwrap(), 0o100755);
+        assert_eq!(script2.mode().unwrap(), 0o100711);
+        assert_eq!(file1.mode().unwrap(), 0o100644);
+        assert_eq!(file2.mode().unwrap(), 0o100644);
+        assert_eq!(dir1.mode().unwrap(), 0o40750);
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_chmod_p_symbolic() {
         let tmpdir = setup().mash("file_chmod_p_symbolic");
@@ -1090,6 +2592,89 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_chown_by_name() {
+        let tmpdir = setup().mash("file_chown_by_name");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("file2");
+        let name = user::name().unwrap();
+        let group = user::group_gid(user::getgid()).unwrap().name;
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::touch(&file1).is_ok());
+
+        // names resolve to the current ids
+        assert!(sys::chown_by_name(&tmpdir, &name, &group).is_ok());
+        assert_eq!(file1.uid().unwrap(), user::getuid());
+        assert_eq!(file1.gid().unwrap(), user::getgid());
+
+        // empty names leave the ids unchanged
+        assert!(sys::chown_by_name(&file1, "", "").is_ok());
+        assert!(sys::chown_by_name(&file1, &name, "").is_ok());
+        assert!(sys::chown_by_name(&file1, "", &group).is_ok());
+        assert_eq!(file1.uid().unwrap(), user::getuid());
+        assert_eq!(file1.gid().unwrap(), user::getgid());
+
+        // globbing applies to each match
+        assert!(sys::touch(&file2).is_ok());
+        assert!(sys::chown_by_name(tmpdir.mash("*"), "", &group).is_ok());
+        assert_eq!(file2.gid().unwrap(), user::getgid());
+
+        // empty names leave differing ids of descendants unchanged
+        if user::is_root() {
+            assert!(sys::chown(&file1, 5000, 5001).is_ok());
+            assert!(sys::chown(&file2, 5002, 5003).is_ok());
+            assert!(sys::chown_by_name(&tmpdir, "", &group).is_ok());
+            assert_eq!((file1.uid().unwrap(), file1.gid().unwrap()), (5000, user::getgid()));
+            assert_eq!((file2.uid().unwrap(), file2.gid().unwrap()), (5002, user::getgid()));
+            assert!(sys::chown_by_name(tmpdir.mash("*"), &name, "").is_ok());
+            assert_eq!((file1.uid().unwrap(), file1.gid().unwrap()), (user::getuid(), user::getgid()));
+            assert_eq!((file2.uid().unwrap(), file2.gid().unwrap()), (user::getuid(), user::getgid()));
+        }
+
+        // unknown names
+        let err = sys::chown_by_name(&file1, "fungus_no_such_user", "").unwrap_err();
+        assert_eq!(err.downcast_ref::<UserError>(), Some(&UserError::does_not_exist_by_name("fungus_no_such_user")));
+        let err = sys::chown_by_name(&file1, "", "fungus_no_such_group").unwrap_err();
+        assert_eq!(err.downcast_ref::<UserError>(), Some(&UserError::group_does_not_exist("fungus_no_such_group")));
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_chunks() {
+        let tmpdir = setup().mash("file_chunks");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("file2");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::write(&file1, "this is a test").is_ok());
+        assert!(sys::touch(&file2).is_ok());
+
+        // read in 4 byte chunks and reassemble
+        let chunks = sys::chunks(&file1, 4).unwrap().collect::<FuResult<Vec<Vec<u8>>>>().unwrap();
+        assert_eq!(chunks.len(), 4);
+        assert!(chunks[..3].iter().all(|x| x.len() == 4));
+        assert_eq!(chunks[3], b"st".to_vec());
+        assert_eq!(chunks.concat(), b"this is a test".to_vec());
+
+        // exact multiple of the chunk size
+        assert_eq!(sys::chunks(&file1, 7).unwrap().count(), 2);
+
+        // empty file and invalid input
+        assert_eq!(sys::chunks(&file2, 4).unwrap().count(), 0);
+        assert!(sys::chunks(&file1, 0).is_err());
+        assert!(sys::chunks(tmpdir.mash("foo"), 4).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_copy_empty() {
         let tmpdir = setup().mash("file_copy_empty");
@@ -1207,141 +2792,674 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
         assert!(sys::mkdir(&tmpdir).is_ok());
 
-        // copy single file
-        assert!(sys::touch(&file1).is_ok());
-        assert_eq!(file1.exists(), true);
-        assert_eq!(file2.exists(), false);
-        assert!(sys::copy(&file1, &file2).is_ok());
+        // copy single file
+        assert!(sys::touch(&file1).is_ok());
+        assert_eq!(file1.exists(), true);
+        assert_eq!(file2.exists(), false);
+        assert!(sys::copy(&file1, &file2).is_ok());
+
+        let paths = vec![tmpdir.mash("file1"), tmpdir.mash("file2")];
+        assert_iter_eq(sys::all_paths(&tmpdir).unwrap(), paths);
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_copy_p_preserve_owner() {
+        let tmpdir = setup().mash("file_copy_p_preserve_owner");
+        let src = tmpdir.mash("src");
+        let dir1 = src.mash("dir1");
+        let file1 = dir1.mash("file1");
+        let link1 = src.mash("link1");
+        let dst = tmpdir.mash("dst");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::touch(&file1).is_ok());
+        assert!(sys::symlink(&link1, "dir1/file1").is_ok());
+
+        // non root silently skips unless strict
+        if !user::is_root() {
+            assert!(sys::copy_p(&src, &dst).unwrap().preserve_owner(true).copy().is_ok());
+            assert_eq!(dst.mash("dir1/file1").uid().unwrap(), user::getuid());
+            let err = sys::copy_p(&src, tmpdir.mash("dst2")).unwrap().preserve_owner(true).strict(true).copy().unwrap_err();
+            assert_eq!(err.downcast_ref::<UserError>(), Some(&UserError::RequiresRoot));
+            assert!(sys::remove_all(&tmpdir).is_ok());
+            return;
+        }
+
+        // root preserves ownership including links themselves
+        assert!(sys::lchown(&src, 5000, 5001).is_ok());
+        assert!(sys::copy_p(&src, &dst).unwrap().preserve_owner(true).copy().is_ok());
+        for path in [dst.clone(), dst.mash("dir1"), dst.mash("dir1/file1")].iter() {
+            assert_eq!((path.uid().unwrap(), path.gid().unwrap()), (5000, 5001));
+        }
+        let meta = fs::symlink_metadata(dst.mash("link1")).unwrap();
+        assert_eq!((meta.uid(), meta.gid()), (5000, 5001));
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_copy_p_deref_dst() {
+        let tmpdir = setup().mash("file_copy_p_deref_dst");
+        let dir1 = tmpdir.mash("dir1");
+        let file1 = dir1.mash("file1");
+        let target = tmpdir.mash("target");
+        let link1 = tmpdir.mash("link1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::mkdir(&target).is_ok());
+        assert!(sys::write(&file1, "this is a test").is_ok());
+        assert!(sys::symlink(&link1, &target).is_ok());
+
+        // follow the destination link by default and nest inside the target
+        assert!(sys::copy(&dir1, &link1).is_ok());
+        assert!(link1.is_symlink_dir());
+        assert_eq!(sys::readstring(target.mash("dir1/file1")).unwrap(), "this is a test");
+
+        // replace the destination link with a clone of the source
+        assert!(sys::remove_all(target.mash("dir1")).is_ok());
+        assert!(sys::copy_p(&dir1, &link1).unwrap().deref_dst(false).copy().is_ok());
+        assert!(!link1.is_symlink());
+        assert!(link1.is_dir());
+        assert_eq!(sys::readstring(link1.mash("file1")).unwrap(), "this is a test");
+        assert!(sys::paths(&target).unwrap().is_empty());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_copy_p_filter_mode_follow_links() {
+        let tmpdir = setup().mash("file_copy_p_filter_mode_follow_links");
+        let src = tmpdir.mash("src");
+        let dst1 = tmpdir.mash("dst1");
+        let dst2 = tmpdir.mash("dst2");
+        let dst3 = tmpdir.mash("dst3");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(src.mash("dir1")).is_ok());
+        assert!(sys::mkdir(src.mash("dir2")).is_ok());
+        assert!(sys::write_p(src.mash("main.rs"), "fn main() {}", 0o755).is_ok());
+        assert!(sys::write_p(src.mash("dir1/lib.rs"), "mod foo;", 0o755).is_ok());
+        assert!(sys::write_p(src.mash("dir1/README.md"), "readme", 0o644).is_ok());
+        assert!(sys::write_p(src.mash("dir2/notes.txt"), "notes", 0o644).is_ok());
+        assert!(sys::symlink(src.mash("link.rs"), "main.rs").is_ok());
+
+        // filter only rust files and strip executable bits
+        let rx = Regex::new(r"\.rs$").unwrap();
+        assert!(sys::copy_p(&src, &dst1).unwrap().filter(rx).mode(0o644).copy().is_ok());
+        let paths = vec![dst1.mash("dir1"), dst1.mash("dir1/lib.rs"), dst1.mash("link.rs"), dst1.mash("main.rs")];
+        assert_iter_eq(sys::all_paths(&dst1).unwrap(), paths);
+        assert_eq!(dst1.mash("main.rs").mode().unwrap(), 0o100644);
+        assert_eq!(dst1.mash("dir1/lib.rs").mode().unwrap(), 0o100644);
+        assert!(dst1.mash("link.rs").is_symlink());
+
+        // links are recreated by default
+        assert!(sys::copy_p(&src, &dst2).unwrap().copy().is_ok());
+        assert!(dst2.mash("link.rs").is_symlink());
+        assert_eq!(dst2.mash("main.rs").mode().unwrap(), 0o100755);
+
+        // follow links copies the link targets
+        assert!(sys::copy_p(&src, &dst3).unwrap().follow_links(true).copy().is_ok());
+        assert!(!dst3.mash("link.rs").is_symlink());
+        assert_eq!(sys::readstring(dst3.mash("link.rs")).unwrap(), "fn main() {}");
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_copy_p_ignore_file() {
+        let tmpdir = setup().mash("file_copy_p_ignore_file");
+        let src = tmpdir.mash("src");
+        let dst = tmpdir.mash("dst");
+        let ignore = tmpdir.mash("ignore");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(src.mash("target/debug")).is_ok());
+        assert!(sys::mkdir(src.mash("src/target")).is_ok());
+        assert!(sys::mkdir(src.mash("docs/build")).is_ok());
+        assert!(sys::touch(src.mash("target/debug/foo")).is_ok());
+        assert!(sys::touch(src.mash("src/lib.rs")).is_ok());
+        assert!(sys::touch(src.mash("src/lib.tmp")).is_ok());
+        assert!(sys::touch(src.mash("src/keep.tmp")).is_ok());
+        assert!(sys::touch(src.mash("docs/build/index.html")).is_ok());
+        assert!(sys::touch(src.mash("docs/readme.md")).is_ok());
+        assert!(sys::touch(src.mash("foo.tmp")).is_ok());
+        assert!(sys::touch(src.mash("target.txt")).is_ok());
+        assert!(sys::write(&ignore, "# build output\ntarget/\n*.tmp\n!keep.tmp\n\n/docs/**/*.html\n").is_ok());
+
+        // copy honoring the ignore rules
+        assert!(sys::copy_p(&src, &dst).unwrap().ignore_file(&ignore).copy().is_ok());
+        let paths = vec![
+            dst.mash("docs"),
+            dst.mash("docs/build"),
+            dst.mash("docs/readme.md"),
+            dst.mash("src"),
+            dst.mash("src/keep.tmp"),
+            dst.mash("src/lib.rs"),
+            dst.mash("target.txt"),
+        ];
+        assert_iter_eq(sys::all_paths(&dst).unwrap(), paths);
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_copy_p_atomic() {
+        let tmpdir = setup().mash("file_copy_p_atomic");
+        let src = tmpdir.mash("src");
+        let dir1 = src.mash("dir1");
+        let file1 = src.mash("file1");
+        let file2 = dir1.mash("file2");
+        let dst = tmpdir.mash("dst");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::write(&file1, "file1").is_ok());
+        assert!(sys::write_p(&file2, "file2", 0o600).is_ok());
+
+        // copy the tree atomically
+        assert_eq!(sys::copy_p(&src, &dst).unwrap().atomic(true).copy().unwrap(), dst);
+        assert_eq!(sys::readstring(dst.mash("file1")).unwrap(), "file1");
+        assert_eq!(sys::readstring(dst.mash("dir1/file2")).unwrap(), "file2");
+        assert_eq!(dst.mash("dir1/file2").mode().unwrap(), 0o100600);
+
+        // no temp files remain
+        let paths = vec![dst.mash("dir1"), dst.mash("dir1/file2"), dst.mash("file1")];
+        assert_iter_eq(sys::all_paths(&dst).unwrap(), paths);
+
+        // overwrite an existing file atomically
+        assert!(sys::write(&file1, "updated").is_ok());
+        assert!(sys::copyfile_p(&file1, dst.mash("file1")).unwrap().atomic(true).copy().is_ok());
+        assert_eq!(sys::readstring(dst.mash("file1")).unwrap(), "updated");
+        assert_eq!(sys::files(&dst).unwrap(), vec![dst.mash("file1")]);
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_copy_p_progress() {
+        let tmpdir = setup().mash("file_copy_p_progress");
+        let src = tmpdir.mash("src");
+        let dir1 = src.mash("dir1");
+        let dst = tmpdir.mash("dst");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::write(src.mash("file1"), "1234").is_ok());
+        assert!(sys::write(dir1.mash("file2"), "123456").is_ok());
+        assert!(sys::write(dir1.mash("file3.rs"), "12").is_ok());
+        assert!(sys::symlink(src.mash("link1"), "file1").is_ok());
+
+        // reports after each file with the final call equal to the tree size
+        let mut calls = vec![];
+        assert!(sys::copy_p(&src, &dst).unwrap().progress(|x, y| calls.push((x, y))).copy().is_ok());
+        assert_eq!(calls, vec![(6, 12), (8, 12), (12, 12)]);
+        assert_eq!(calls.last().unwrap().0, sys::size(&src).unwrap());
+        assert_eq!(sys::size(&dst).unwrap(), 12);
+
+        // total only includes filtered files
+        let mut calls = vec![];
+        let rx = Regex::new(r".*\.rs$").unwrap();
+        assert!(sys::copy_p(&src, tmpdir.mash("dst2")).unwrap().filter(rx).progress(|x, y| calls.push((x, y))).copy().is_ok());
+        assert_eq!(calls, vec![(2, 2)]);
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_copyfile() {
+        let tmpdir = setup().mash("file_copyfile");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("file2");
+        let link1 = tmpdir.mash("link1");
+        let link2 = tmpdir.mash("link2");
+        let file3 = tmpdir.mash("dir1/file3");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // copy to same dir
+        assert!(sys::touch(&file1).is_ok());
+        assert_eq!(file1.exists(), true);
+        assert_eq!(file2.exists(), false);
+        assert!(sys::copyfile(&file1, &file2).is_ok());
+        assert_eq!(file2.exists(), true);
+        assert_eq!(file1.mode().unwrap(), file2.mode().unwrap());
+
+        // copy a link
+        assert!(sys::symlink(&link1, &file1).is_ok());
+        assert_eq!(link2.exists(), false);
+        assert!(sys::copyfile(&link1, &link2).is_ok());
+        assert_eq!(link2.exists(), true);
+
+        // copy to dir the doesn't exist
+        assert_eq!(file3.exists(), false);
+        assert!(sys::copyfile(&file1, &file3).is_ok());
+        assert_eq!(file3.exists(), true);
+        assert_eq!(tmpdir.mode().unwrap(), file3.dir().unwrap().mode().unwrap());
+        assert_eq!(file1.mode().unwrap(), file3.mode().unwrap());
+
+        // copy into an existing dir only updates the dir not the files already in it
+        let file4 = tmpdir.mash("dir1/file4");
+        assert!(sys::chmod(&file3, 0o600).is_ok());
+        assert!(sys::copyfile(&file1, &file4).is_ok());
+        assert_eq!(file3.mode().unwrap(), 0o100600);
+        assert_eq!(file1.mode().unwrap(), file4.mode().unwrap());
+
+        // empty destination path
+        assert!(sys::copyfile(&file1, "").is_err());
+
+        // empty source path
+        assert!(sys::copyfile("", &file3).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_copyfile_p() {
+        let tmpdir = setup().mash("file_copyfile_p");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("file2");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // copy to same dir
+        assert!(sys::touch_p(&file1, 0o644).is_ok());
+        assert!(sys::copyfile_p(&file1, &file2).unwrap().mode(0o555).copy().is_ok());
+        assert_eq!(file2.mode().unwrap(), 0o100555);
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_copyfile_p_preserve_times() {
+        let tmpdir = setup().mash("file_copyfile_p_preserve_times");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("file2");
+        let file3 = tmpdir.mash("file3");
+        let file4 = tmpdir.mash("file4");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::write(&file1, "this is a test").is_ok());
+        std::thread::sleep(std::time::Duration::from_millis(50));
+
+        // times are not preserved by default
+        assert!(sys::copyfile_p(&file1, &file2).unwrap().copy().is_ok());
+        let (src, dst) = (file1.metadata().unwrap(), file2.metadata().unwrap());
+        assert_ne!((src.mtime(), src.mtime_nsec()), (dst.mtime(), dst.mtime_nsec()));
+
+        // preserved times match the source
+        assert!(sys::copyfile_p(&file1, &file3).unwrap().preserve_times(true).copy().is_ok());
+        let dst = file3.metadata().unwrap();
+        assert_eq!((src.mtime(), src.mtime_nsec()), (dst.mtime(), dst.mtime_nsec()));
+        assert_eq!(src.modified().unwrap(), dst.modified().unwrap());
+
+        // preserved with atomic copies as well
+        assert!(sys::copyfile_p(&file1, &file4).unwrap().atomic(true).preserve_times(true).copy().is_ok());
+        assert_eq!(src.modified().unwrap(), file4.metadata().unwrap().modified().unwrap());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_digest() {
+        let tmpdir = setup().mash("file_digest");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("file2");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // test
+        assert!(sys::write(&file1, "this is a test").is_ok());
+        assert!(sys::copyfile(&file1, &file2).is_ok());
+        assert_iter_eq(sys::digest(&file1).unwrap(), sys::digest(&file2).unwrap());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_digest_all() {
+        let tmpdir = setup().mash("file_digest_all");
+        let dir1 = tmpdir.mash("dir1");
+        let dir2 = tmpdir.mash("dir2");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(dir1.mash("sub1")).is_ok());
+        assert!(sys::write(dir1.mash("file1"), "this is a test").is_ok());
+        assert!(sys::write(dir1.mash("sub1/file2"), "this is another test").is_ok());
+        assert!(sys::symlink(dir1.mash("link1"), "file1").is_ok());
+
+        // identical trees produce identical digests
+        assert!(sys::copy(&dir1, &dir2).is_ok());
+        let digest = sys::digest_all(&dir1).unwrap();
+        assert_eq!(sys::digest_all(&dir2).unwrap(), digest);
+
+        // content, name and link target changes are detected
+        assert!(sys::write(dir2.mash("sub1/file2"), "changed").is_ok());
+        assert_ne!(sys::digest_all(&dir2).unwrap(), digest);
+        assert!(sys::remove_all(&dir2).is_ok());
+        assert!(sys::copy(&dir1, &dir2).is_ok());
+        assert!(sys::move_p(dir2.mash("file1"), dir2.mash("file3")).is_ok());
+        assert_ne!(sys::digest_all(&dir2).unwrap(), digest);
+        assert!(sys::remove_all(&dir2).is_ok());
+        assert!(sys::copy(&dir1, &dir2).is_ok());
+        assert!(fs::remove_file(dir2.mash("link1")).is_ok());
+        assert!(sys::symlink(dir2.mash("link1"), "sub1/file2").is_ok());
+        assert_ne!(sys::digest_all(&dir2).unwrap(), digest);
+
+        // files aren't directories
+        assert!(sys::digest_all(dir1.mash("file1")).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_digest_stream() {
+        let tmpdir = setup().mash("file_digest_stream");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("file2");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // larger than the chunk size matches hashing the whole file at once
+        let data: Vec<u8> = (0..200 * 1024).map(|x| (x % 251) as u8).collect();
+        assert!(sys::write(&file1, &data).is_ok());
+        let expected: Vec<u8> = Blake2b::digest(&data).into_iter().collect();
+        assert_eq!(sys::digest_stream(&file1).unwrap(), expected);
+        assert_eq!(sys::digest(&file1).unwrap(), expected);
+
+        // empty file
+        assert!(sys::touch(&file2).is_ok());
+        let expected: Vec<u8> = Blake2b::digest(b"").into_iter().collect();
+        assert_eq!(sys::digest_stream(&file2).unwrap(), expected);
+
+        // missing file
+        assert!(sys::digest_stream(tmpdir.mash("foo")).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_empty_dir() {
+        let tmpdir = setup().mash("file_empty_dir");
+        let dir1 = tmpdir.mash("dir1");
+        let dir2 = tmpdir.mash("dir2");
+        let file1 = dir1.mash("file1");
+        let file2 = dir2.mash("file2");
+        let link1 = dir1.mash("link1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(dir1.mash("sub1/sub2")).is_ok());
+        assert!(sys::mkdir_p(&dir2, 0o750).is_ok());
+        assert!(sys::touch(&file1).is_ok());
+        assert!(sys::touch(dir1.mash("sub1/sub2/file3")).is_ok());
+        assert!(sys::touch(&file2).is_ok());
+        assert!(sys::symlink(&link1, &dir2).is_ok());
+
+        // children are removed without following links
+        assert!(sys::empty_dir(&dir1).is_ok());
+        assert!(dir1.is_dir());
+        assert!(sys::paths(&dir1).unwrap().is_empty());
+        assert!(file2.exists());
+
+        // mode is preserved
+        assert!(sys::empty_dir(&dir2).is_ok());
+        assert_eq!(dir2.mode().unwrap(), 0o40750);
+        assert!(sys::paths(&dir2).unwrap().is_empty());
+
+        // doesn't exist or isn't a dir
+        assert!(sys::touch(&file1).is_ok());
+        assert_eq!(sys::empty_dir(&file1).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::is_not_dir(&file1)));
+        assert_eq!(sys::empty_dir(tmpdir.mash("foo")).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::does_not_exist(tmpdir.mash("foo"))));
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_extract_string() {
+        let tmpdir = setup().mash("file_extract_string");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("file2");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // single line
+        let rx = Regex::new(r"'([^']+)'.*").unwrap();
+        assert!(sys::write(&file1, "Not my favorite movie: 'Citizen Kane' (1941).").is_ok());
+        assert_eq!(sys::extract_string(&file1, &rx).unwrap(), "Citizen Kane");
+        assert_eq!(sys::extract_string_p(&file1, r"'([^']+)'.*").unwrap(), "Citizen Kane");
+
+        // multi line
+        assert!(sys::write(&file2, "# test\npkgbase=linux\npkgver=5.4.8.arch1\npkgrel=1\n").is_ok());
+        assert_eq!(sys::extract_string_p(&file2, r"(?m)^pkgver=(\d+\.\d+\.\d+).*").unwrap(), "5.4.8");
+
+        // invalid pattern
+        let err = sys::extract_string_p(&file1, "(").unwrap_err();
+        assert!(err.to_string().contains("invalid regex pattern '('"));
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_extract_strings() {
+        let tmpdir = setup().mash("file_extract_strings");
+        let file1 = tmpdir.mash("file1");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // extract_string
+        let rx = Regex::new(r"'([^']+)'\s+\((\d{4})\)").unwrap();
+        assert!(sys::write(&file1, "Not my favorite movie: 'Citizen Kane' (1941).").is_ok());
+        assert_eq!(sys::extract_strings(&file1, &rx).unwrap(), vec!["Citizen Kane", "1941"]);
+
+        // extract_string_p
+        assert_eq!(sys::extract_strings_p(&file1, r"'([^']+)'\s+\((\d{4})\)").unwrap(), vec!["Citizen Kane", "1941"]);
 
-        let paths = vec![tmpdir.mash("file1"), tmpdir.mash("file2")];
-        assert_iter_eq(sys::all_paths(&tmpdir).unwrap(), paths);
+        // invalid pattern
+        let err = sys::extract_strings_p(&file1, "(").unwrap_err();
+        assert!(err.to_string().contains("invalid regex pattern '('"));
+
+        // none
+        let rx = Regex::new(r"(foo)").unwrap();
+        assert!(sys::extract_strings(&file1, &rx).is_err());
 
         // cleanup
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
     #[test]
-    fn test_copyfile() {
-        let tmpdir = setup().mash("file_copyfile");
+    fn test_filetype() {
+        let tmpdir = setup().mash("file_filetype");
         let file1 = tmpdir.mash("file1");
         let file2 = tmpdir.mash("file2");
-        let link1 = tmpdir.mash("link1");
-        let link2 = tmpdir.mash("link2");
-        let file3 = tmpdir.mash("dir1/file3");
+        let file3 = tmpdir.mash("file3");
+        let file4 = tmpdir.mash("file4");
 
         // setup
         assert!(sys::remove_all(&tmpdir).is_ok());
         assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::write(&file1, "this is a test\n".repeat(100)).is_ok());
+        assert!(sys::write(&file2, b"PK\x03\x04\x14\x00").is_ok());
+        assert!(sys::write(&file3, b"foo\x00bar").is_ok());
+        let mut data = "a".repeat(1023).into_bytes();
+        data.extend("é".as_bytes());
+        assert!(sys::write(&file4, &data).is_ok());
 
-        // copy to same dir
-        assert!(sys::touch(&file1).is_ok());
-        assert_eq!(file1.exists(), true);
-        assert_eq!(file2.exists(), false);
-        assert!(sys::copyfile(&file1, &file2).is_ok());
-        assert_eq!(file2.exists(), true);
-        assert_eq!(file1.mode().unwrap(), file2.mode().unwrap());
-
-        // copy a link
-        assert!(sys::symlink(&link1, &file1).is_ok());
-        assert_eq!(link2.exists(), false);
-        assert!(sys::copyfile(&link1, &link2).is_ok());
-        assert_eq!(link2.exists(), true);
-
-        // copy to dir the doesn't exist
-        assert_eq!(file3.exists(), false);
-        assert!(sys::copyfile(&file1, &file3).is_ok());
-        assert_eq!(file3.exists(), true);
-        assert_eq!(tmpdir.mode().unwrap(), file3.dir().unwrap().mode().unwrap());
-        assert_eq!(file1.mode().unwrap(), file3.mode().unwrap());
-
-        // empty destination path
-        assert!(sys::copyfile(&file1, "").is_err());
-
-        // empty source path
-        assert!(sys::copyfile("", &file3).is_err());
+        // test
+        assert_eq!(sys::filetype("tests/alpine-base.tgz").unwrap(), sys::FileType::Gzip);
+        assert_eq!(sys::filetype("tests/alpine-base.tar").unwrap(), sys::FileType::Tar);
+        assert_eq!(sys::filetype(sys::exe().unwrap()).unwrap(), sys::FileType::Elf);
+        assert_eq!(sys::filetype(&file1).unwrap(), sys::FileType::Text);
+        assert_eq!(sys::filetype(&file2).unwrap(), sys::FileType::Zip);
+        assert_eq!(sys::filetype(&file3).unwrap(), sys::FileType::Unknown);
+        assert_eq!(sys::filetype(&file4).unwrap(), sys::FileType::Text);
+        assert_eq!(sys::FileType::Gzip.to_string(), "gzip");
+        assert!(sys::filetype(tmpdir.mash("foo")).is_err());
 
         // cleanup
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
     #[test]
-    fn test_copyfile_p() {
-        let tmpdir = setup().mash("file_copyfile_p");
+    fn test_for_each_line() {
+        let tmpdir = setup().mash("file_for_each_line");
         let file1 = tmpdir.mash("file1");
-        let file2 = tmpdir.mash("file2");
 
         // setup
         assert!(sys::remove_all(&tmpdir).is_ok());
         assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::write(&file1, "one\ntwo\nthree\nfour").is_ok());
+
+        // sum line lengths without collecting
+        let mut sum = 0;
+        assert!(sys::for_each_line(&file1, |line| {
+            sum += line.len();
+            Ok(())
+        })
+        .is_ok());
+        assert_eq!(sum, 15);
+
+        // stops on the first callback error
+        let mut lines = vec![];
+        let err = sys::for_each_line(&file1, |line| {
+            if line == "three" {
+                return Err(StringError::FailedToString.into());
+            }
+            lines.push(line.to_string());
+            Ok(())
+        })
+        .unwrap_err();
+        assert_eq!(err.downcast_ref::<StringError>(), Some(&StringError::FailedToString));
+        assert_iter_eq(lines, vec!["one", "two"]);
 
-        // copy to same dir
-        assert!(sys::touch_p(&file1, 0o644).is_ok());
-        assert!(sys::copyfile_p(&file1, &file2).unwrap().mode(0o555).copy().is_ok());
-        assert_eq!(file2.mode().unwrap(), 0o100555);
+        // missing file
+        assert!(sys::for_each_line(tmpdir.mash("foo"), |_| Ok(())).is_err());
 
         // cleanup
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
     #[test]
-    fn test_digest() {
-        let tmpdir = setup().mash("file_digest");
+    fn test_grep() {
+        let tmpdir = setup().mash("file_grep");
         let file1 = tmpdir.mash("file1");
-        let file2 = tmpdir.mash("file2");
 
         // setup
         assert!(sys::remove_all(&tmpdir).is_ok());
         assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::write(&file1, "#!/bin/bash\nfoo=1\n\n# comment\nbar=2\nfoo=3").is_ok());
 
-        // test
-        assert!(sys::write(&file1, "this is a test").is_ok());
-        assert!(sys::copyfile(&file1, &file2).is_ok());
-        assert_iter_eq(sys::digest(&file1).unwrap(), sys::digest(&file2).unwrap());
+        // matches
+        let rx = Regex::new(r"^foo=(\d)").unwrap();
+        assert_eq!(sys::grep(&file1, &rx).unwrap(), vec![(2, "foo=1".to_string()), (6, "foo=3".to_string())]);
+        assert_eq!(sys::grep_p(&file1, r"^#").unwrap(), vec![(1, "#!/bin/bash".to_string()), (4, "# comment".to_string())]);
+        assert_eq!(sys::grep_p(&file1, r"^$").unwrap(), vec![(3, "".to_string())]);
+
+        // no matches
+        assert!(sys::grep_p(&file1, r"blah").unwrap().is_empty());
+
+        // errors
+        assert!(sys::grep_p(&file1, r"(foo").is_err());
+        assert!(sys::grep(tmpdir.mash("foo"), &rx).is_err());
 
         // cleanup
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
     #[test]
-    fn test_extract_string() {
-        let tmpdir = setup().mash("file_extract_string");
+    fn test_head() {
+        let tmpdir = setup().mash("file_head");
         let file1 = tmpdir.mash("file1");
         let file2 = tmpdir.mash("file2");
+
+        // setup
         assert!(sys::remove_all(&tmpdir).is_ok());
         assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::write(&file1, "one\ntwo\nthree\n").is_ok());
+        assert!(sys::touch(&file2).is_ok());
 
-        // single line
-        let rx = Regex::new(r"'([^']+)'.*").unwrap();
-        assert!(sys::write(&file1, "Not my favorite movie: 'Citizen Kane' (1941).").is_ok());
-        assert_eq!(sys::extract_string(&file1, &rx).unwrap(), "Citizen Kane");
-        assert_eq!(sys::extract_string_p(&file1, r"'([^']+)'.*").unwrap(), "Citizen Kane");
+        // boundaries
+        assert!(sys::head(&file1, 0).unwrap().is_empty());
+        assert_iter_eq(sys::head(&file1, 1).unwrap(), vec!["one"]);
+        assert_iter_eq(sys::head(&file1, 2).unwrap(), vec!["one", "two"]);
+        assert_iter_eq(sys::head(&file1, 3).unwrap(), vec!["one", "two", "three"]);
+        assert_iter_eq(sys::head(&file1, 10).unwrap(), vec!["one", "two", "three"]);
+        assert!(sys::head(&file2, 10).unwrap().is_empty());
 
-        // multi line
-        assert!(sys::write(&file2, "# test\npkgbase=linux\npkgver=5.4.8.arch1\npkgrel=1\n").is_ok());
-        assert_eq!(sys::extract_string_p(&file2, r"(?m)^pkgver=(\d+\.\d+\.\d+).*").unwrap(), "5.4.8");
+        // missing file
+        assert!(sys::head(tmpdir.mash("foo"), 1).is_err());
 
         // cleanup
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
     #[test]
-    fn test_extract_strings() {
-        let tmpdir = setup().mash("file_extract_strings");
+    fn test_line_count() {
+        let tmpdir = setup().mash("file_line_count");
         let file1 = tmpdir.mash("file1");
+
+        // setup
         assert!(sys::remove_all(&tmpdir).is_ok());
         assert!(sys::mkdir(&tmpdir).is_ok());
 
-        // extract_string
-        let rx = Regex::new(r"'([^']+)'\s+\((\d{4})\)").unwrap();
-        assert!(sys::write(&file1, "Not my favorite movie: 'Citizen Kane' (1941).").is_ok());
-        assert_eq!(sys::extract_strings(&file1, &rx).unwrap(), vec!["Citizen Kane", "1941"]);
+        // empty file
+        assert!(sys::touch(&file1).is_ok());
+        assert_eq!(sys::line_count(&file1).unwrap(), 0);
 
-        // extract_string_p
-        assert_eq!(sys::extract_strings_p(&file1, r"'([^']+)'\s+\((\d{4})\)").unwrap(), vec!["Citizen Kane", "1941"]);
+        // single line with and without a trailing newline
+        assert!(sys::write(&file1, "one").is_ok());
+        assert_eq!(sys::line_count(&file1).unwrap(), 1);
+        assert!(sys::write(&file1, "one\n").is_ok());
+        assert_eq!(sys::line_count(&file1).unwrap(), 1);
 
-        // none
-        let rx = Regex::new(r"(foo)").unwrap();
-        assert!(sys::extract_strings(&file1, &rx).is_err());
+        // multiple lines with and without a trailing newline
+        assert!(sys::write(&file1, "one\ntwo\n\nfour").is_ok());
+        assert_eq!(sys::line_count(&file1).unwrap(), 4);
+        assert!(sys::write(&file1, "one\ntwo\n\nfour\n").is_ok());
+        assert_eq!(sys::line_count(&file1).unwrap(), 4);
+
+        // larger than the read buffer
+        assert!(sys::write(&file1, "foo\n".repeat(10000)).is_ok());
+        assert_eq!(sys::line_count(&file1).unwrap(), 10000);
+        assert_eq!(sys::line_count(&file1).unwrap(), sys::readlines(&file1).unwrap().len());
+
+        // missing file
+        assert!(sys::line_count(tmpdir.mash("foo")).is_err());
 
         // cleanup
         assert!(sys::remove_all(&tmpdir).is_ok());
@@ -1366,6 +3484,75 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_mkdir_m() {
+        let tmpdir = setup().mash("file_mkdir_m");
+        let dir1 = tmpdir.mash("dir1");
+        let dir2 = dir1.mash("dir2");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir_p(&tmpdir, 0o755).is_ok());
+
+        // test
+        assert_eq!(sys::mkdir_m(&dir2, 0o700).unwrap(), dir2);
+        assert_eq!(tmpdir.mode().unwrap(), 0o40755);
+        assert_eq!(dir1.mode().unwrap(), 0o40700);
+        assert_eq!(dir2.mode().unwrap(), 0o40700);
+
+        // existing directories are left untouched
+        assert!(sys::mkdir_m(&dir2, 0o555).is_ok());
+        assert_eq!(dir2.mode().unwrap(), 0o40700);
+
+        // nested creation with a mode lacking owner write
+        let dir3 = tmpdir.mash("dir3");
+        let dir5 = dir3.mash("dir4/dir5");
+        assert_eq!(sys::mkdir_m(&dir5, 0o555).unwrap(), dir5);
+        assert_eq!(dir3.mode().unwrap(), 0o40555);
+        assert_eq!(dir3.mash("dir4").mode().unwrap(), 0o40555);
+        assert_eq!(dir5.mode().unwrap(), 0o40555);
+        assert!(sys::chmod(&dir3, 0o755).is_ok());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_move_across() {
+        let tmpdir = setup().mash("file_move_across");
+        let dir1 = tmpdir.mash("dir1");
+        let dir2 = tmpdir.mash("dir2");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("file2");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir_p(dir1.mash("sub1"), 0o750).is_ok());
+        assert!(sys::write_p(dir1.mash("file1"), "1234", 0o755).is_ok());
+        assert!(sys::write(dir1.mash("sub1/file2"), "123456").is_ok());
+        assert!(sys::symlink(dir1.mash("link1"), "file1").is_ok());
+        assert!(sys::write_p(&file1, "12", 0o600).is_ok());
+
+        // move a directory preserving modes and links
+        let mut sizes = vec![];
+        assert!(move_across(&dir1, &dir2, |x| sizes.push(x)).is_ok());
+        assert_eq!(sizes, vec![4, 6]);
+        assert!(!dir1.exists());
+        assert_eq!(dir2.mash("sub1").mode().unwrap(), 0o40750);
+        assert_eq!(dir2.mash("file1").mode().unwrap(), 0o100755);
+        assert_eq!(sys::readstring(dir2.mash("sub1/file2")).unwrap(), "123456");
+        assert_eq!(sys::readlink(dir2.mash("link1")).unwrap(), PathBuf::from("file1"));
+
+        // move a single file
+        assert!(move_across(&file1, &file2, |_| {}).is_ok());
+        assert!(!file1.exists());
+        assert_eq!(file2.mode().unwrap(), 0o100600);
+        assert_eq!(sys::readstring(&file2).unwrap(), "12");
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_move_p() {
         let tmpdir = setup().mash("file_move_p");
@@ -1407,6 +3594,130 @@ mod tests {
         assert_eq!(dir3.mash("dir2").exists(), true);
         assert_eq!(dir3.mash("dir2/file2").exists(), true);
 
+        // move globbed files into dir3
+        assert!(sys::touch(tmpdir.mash("file3.txt")).is_ok());
+        assert!(sys::touch(tmpdir.mash("file4.txt")).is_ok());
+        assert!(sys::move_p(tmpdir.mash("*.txt"), &dir3).is_ok());
+        assert!(!tmpdir.mash("file3.txt").exists());
+        assert!(!tmpdir.mash("file4.txt").exists());
+        assert!(dir3.mash("file3.txt").exists());
+        assert!(dir3.mash("file4.txt").exists());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_mover() {
+        let tmpdir = setup().mash("file_mover");
+        let dir1 = tmpdir.mash("dir1");
+        let dir2 = tmpdir.mash("dir2");
+        let dir3 = tmpdir.mash("dir3");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(dir1.mash("sub1")).is_ok());
+        assert!(sys::write(dir1.mash("file1"), "1234").is_ok());
+        assert!(sys::write(dir1.mash("sub1/file2"), "123456").is_ok());
+        assert!(sys::symlink(dir1.mash("link1"), "file1").is_ok());
+
+        // same device rename reports completion immediately
+        let mut calls = vec![];
+        assert!(sys::mover(&dir1, &dir2).unwrap().progress(|x, y| calls.push((x, y))).mv().is_ok());
+        assert_eq!(calls, vec![(10, 10)]);
+        assert!(!dir1.exists());
+        assert_eq!(sys::readstring(dir2.mash("sub1/file2")).unwrap(), "123456");
+
+        // forced copy fallback reports per file and sums to the total
+        let mut calls = vec![];
+        assert!(sys::mover(&dir2, &dir3).unwrap().across(true).progress(|x, y| calls.push((x, y))).mv().is_ok());
+        assert_eq!(calls, vec![(4, 10), (10, 10)]);
+        assert!(!dir2.exists());
+        assert_eq!(sys::readstring(dir3.mash("file1")).unwrap(), "1234");
+        assert_eq!(sys::readstring(dir3.mash("sub1/file2")).unwrap(), "123456");
+        assert_eq!(sys::readlink(dir3.mash("link1")).unwrap(), PathBuf::from("file1"));
+
+        // unreadable subdirs don't block a rename without progress, root can read them anyway
+        if !user::is_root() {
+            let dir4 = tmpdir.mash("dir4");
+            assert!(sys::mkdir_p(dir3.mash("sub2"), 0o000).is_ok());
+            assert!(sys::mover(&dir3, &dir4).unwrap().progress(|_, _| {}).mv().is_err());
+            assert!(sys::move_p(&dir3, &dir4).is_ok());
+            assert!(!dir3.exists());
+            assert!(sys::chmod_p(dir4.mash("sub2")).unwrap().recurse(false).mode(0o755).chmod().is_ok());
+        }
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_overlay() {
+        let tmpdir = setup().mash("file_overlay");
+        let dir1 = tmpdir.mash("dir1");
+        let dir2 = tmpdir.mash("dir2");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(dir1.mash("sub1/sub2")).is_ok());
+        assert!(sys::mkdir(dir2.mash("sub1")).is_ok());
+        assert!(sys::mkdir(dir2.mash("other")).is_ok());
+        assert!(sys::write(dir1.mash("file1"), "src1").is_ok());
+        assert!(sys::write(dir1.mash("sub1/file2"), "src2").is_ok());
+        assert!(sys::write(dir1.mash("sub1/sub2/file3"), "src3").is_ok());
+        assert!(sys::write(dir2.mash("file1"), "dst1").is_ok());
+        assert!(sys::write(dir2.mash("sub1/file4"), "dst4").is_ok());
+        assert!(sys::write(dir2.mash("other/file5"), "dst5").is_ok());
+
+        // src must be an existing directory
+        assert!(sys::overlay(tmpdir.mash("foo"), &dir2).is_err());
+        assert!(sys::overlay(dir1.mash("file1"), &dir2).is_err());
+
+        // overlapping files are overwritten and disjoint files preserved
+        assert!(sys::overlay(&dir1, &dir2).is_ok());
+        assert_eq!(sys::readstring(dir2.mash("file1")).unwrap(), "src1");
+        assert_eq!(sys::readstring(dir2.mash("sub1/file2")).unwrap(), "src2");
+        assert_eq!(sys::readstring(dir2.mash("sub1/sub2/file3")).unwrap(), "src3");
+        assert_eq!(sys::readstring(dir2.mash("sub1/file4")).unwrap(), "dst4");
+        assert_eq!(sys::readstring(dir2.mash("other/file5")).unwrap(), "dst5");
+        assert!(!dir2.mash("dir1").exists());
+
+        // overlay onto a missing destination creates it
+        let dir3 = tmpdir.mash("dir3");
+        assert!(sys::overlay(&dir1, &dir3).is_ok());
+        assert_eq!(sys::readstring(dir3.mash("sub1/sub2/file3")).unwrap(), "src3");
+
+        // a destination that isn't a directory is left alone
+        let file6 = tmpdir.mash("file6");
+        assert!(sys::write(&file6, "dst6").is_ok());
+        assert!(sys::overlay(&dir1, &file6).is_err());
+        assert_eq!(sys::readstring(&file6).unwrap(), "dst6");
+
+        // links to directories in the destination are merged through
+        let dir4 = tmpdir.mash("dir4");
+        let real = tmpdir.mash("real");
+        assert!(sys::mkdir(&dir4).is_ok());
+        assert!(sys::mkdir(&real).is_ok());
+        assert!(sys::write(real.mash("file7"), "dst7").is_ok());
+        assert!(sys::symlink(dir4.mash("sub1"), &real).is_ok());
+        assert!(sys::overlay(&dir1, &dir4).is_ok());
+        assert!(dir4.mash("sub1").is_symlink());
+        assert_eq!(sys::readstring(real.mash("file7")).unwrap(), "dst7");
+        assert_eq!(sys::readstring(real.mash("file2")).unwrap(), "src2");
+        assert_eq!(sys::readstring(real.mash("sub2/file3")).unwrap(), "src3");
+
+        // type conflicts are errors rather than removals
+        let dir5 = tmpdir.mash("dir5");
+        assert!(sys::mkdir(dir5.mash("file1")).is_ok());
+        assert!(sys::write(dir5.mash("file1/file8"), "dst8").is_ok());
+        assert!(sys::overlay(&dir1, &dir5).is_err());
+        assert_eq!(sys::readstring(dir5.mash("file1/file8")).unwrap(), "dst8");
+        let dir6 = tmpdir.mash("dir6");
+        assert!(sys::mkdir(&dir6).is_ok());
+        assert!(sys::write(dir6.mash("sub1"), "dst9").is_ok());
+        assert!(sys::overlay(&dir1, &dir6).is_err());
+        assert_eq!(sys::readstring(dir6.mash("sub1")).unwrap(), "dst9");
+
         // cleanup
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
@@ -1428,6 +3739,27 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_readchunk() {
+        let tmpdir = setup().mash("file_readchunk");
+        let tmpfile = tmpdir.mash("file1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::write(&tmpfile, "0123456789abcdefghij").is_ok());
+
+        // test
+        assert_eq!(sys::readchunk(&tmpfile, 5, 5).unwrap(), b"56789".to_vec());
+        assert_eq!(sys::readchunk(&tmpfile, 0, 3).unwrap(), b"012".to_vec());
+        assert_eq!(sys::readchunk(&tmpfile, 17, 10).unwrap(), b"hij".to_vec());
+        assert!(sys::readchunk(&tmpfile, 30, 10).unwrap().is_empty());
+        assert!(sys::readchunk(tmpdir.mash("foo"), 0, 10).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_readlines() {
         let tmpdir = setup().mash("file_readlines");
@@ -1479,6 +3811,39 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_prepend() {
+        let tmpdir = setup().mash("file_prepend");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("file2");
+        let file3 = tmpdir.mash("file3");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // existing file keeps its mode
+        assert!(sys::write_p(&file1, "bar\n", 0o600).is_ok());
+        assert!(sys::prepend(&file1, "#!/bin/bash\n").is_ok());
+        assert_eq!(sys::readstring(&file1).unwrap(), "#!/bin/bash\nbar\n");
+        assert_eq!(file1.mode().unwrap(), 0o100600);
+
+        // empty file
+        assert!(sys::touch(&file2).is_ok());
+        assert!(sys::prepend(&file2, "foo").is_ok());
+        assert_eq!(sys::readstring(&file2).unwrap(), "foo");
+
+        // missing file is created
+        assert!(sys::prepend(&file3, "foo").is_ok());
+        assert_eq!(sys::readstring(&file3).unwrap(), "foo");
+
+        // no temp files left behind
+        assert_iter_eq(sys::all_paths(&tmpdir).unwrap(), vec![file1, file2, file3]);
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_remove() {
         let tmpdir = setup().mash("file_remove_dir");
@@ -1507,6 +3872,38 @@ mod tests {
         assert_eq!(tmpdir.exists(), false);
     }
 
+    #[test]
+    fn test_remove_files() {
+        let tmpdir = setup().mash("file_remove_files");
+        let dir1 = tmpdir.mash("dir1.log");
+        let file1 = tmpdir.mash("file1.log");
+        let file2 = tmpdir.mash("file2.txt");
+        let file3 = dir1.mash("file3.log");
+        let link1 = tmpdir.mash("link1.log");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::touch(&file1).is_ok());
+        assert!(sys::touch(&file2).is_ok());
+        assert!(sys::touch(&file3).is_ok());
+        assert!(sys::symlink(&link1, &file2).is_ok());
+
+        // only files and links matching the glob are removed
+        assert_eq!(sys::remove_files(tmpdir.mash("*.log")).unwrap(), vec![file1.clone(), link1.clone()]);
+        assert!(!file1.exists());
+        assert!(!link1.is_symlink());
+        assert!(dir1.is_dir());
+        assert!(file2.exists());
+        assert!(file3.exists());
+
+        // no matches is not an error
+        assert!(sys::remove_files(tmpdir.mash("*.foo")).unwrap().is_empty());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_revoking() {
         // test other octet
@@ -1552,6 +3949,33 @@ mod tests {
         assert_eq!(tmpdir.exists(), false);
     }
 
+    #[test]
+    fn test_tail() {
+        let tmpdir = setup().mash("file_tail");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("file2");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        assert!(sys::write(&file1, "one\ntwo\nthree").is_ok());
+        assert!(sys::touch(&file2).is_ok());
+
+        // boundaries
+        assert!(sys::tail(&file1, 0).unwrap().is_empty());
+        assert_iter_eq(sys::tail(&file1, 1).unwrap(), vec!["three"]);
+        assert_iter_eq(sys::tail(&file1, 2).unwrap(), vec!["two", "three"]);
+        assert_iter_eq(sys::tail(&file1, 3).unwrap(), vec!["one", "two", "three"]);
+        assert_iter_eq(sys::tail(&file1, 10).unwrap(), vec!["one", "two", "three"]);
+        assert!(sys::tail(&file2, 10).unwrap().is_empty());
+
+        // missing file
+        assert!(sys::tail(tmpdir.mash("foo"), 1).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_touch() {
         let tmpdir = setup().mash("file_touch");
@@ -1568,6 +3992,31 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_touch_m() {
+        let tmpdir = setup().mash("file_touch_m");
+        let tmpfile = tmpdir.mash("file1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // creates missing files
+        assert_eq!(sys::touch_m(&tmpfile).unwrap(), tmpfile);
+        assert!(tmpfile.is_file());
+        let mtime = tmpfile.metadata().unwrap().modified().unwrap();
+
+        // touch leaves existing times alone while touch_m advances them
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert!(sys::touch(&tmpfile).is_ok());
+        assert_eq!(tmpfile.metadata().unwrap().modified().unwrap(), mtime);
+        assert!(sys::touch_m(&tmpfile).is_ok());
+        assert!(tmpfile.metadata().unwrap().modified().unwrap() > mtime);
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_touch_p() {
         let tmpdir = setup().mash("file_touch_p");
@@ -1602,6 +4051,78 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_write_atomic() {
+        let tmpdir = setup().mash("file_write_atomic");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("file2");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // new file
+        assert!(sys::write_atomic(&file1, "this is a test").is_ok());
+        assert_eq!(sys::readstring(&file1).unwrap(), "this is a test");
+
+        // existing file keeps its mode
+        assert!(sys::write_p(&file2, "foo", 0o600).is_ok());
+        assert!(sys::write_atomic(&file2, "this is another test").is_ok());
+        assert_eq!(sys::readstring(&file2).unwrap(), "this is another test");
+        assert_eq!(file2.mode().unwrap(), 0o100600);
+        assert!(sys::chmod(&file2, 0o750).is_ok());
+        assert!(sys::write_atomic(&file2, "this is a third test").is_ok());
+        assert_eq!(file2.mode().unwrap(), 0o100750);
+
+        // no temp files left behind
+        assert_iter_eq(sys::all_paths(&tmpdir).unwrap(), vec![file1, file2.clone()]);
+
+        // missing directory fails without leaving anything behind
+        assert!(sys::write_atomic(tmpdir.mash("foo/file3"), "foo").is_err());
+        assert_eq!(sys::all_paths(&tmpdir).unwrap().len(), 2);
+
+        // links are preserved while their target is replaced
+        let dir1 = tmpdir.mash("dir1");
+        let link1 = tmpdir.mash("link1");
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::symlink(&link1, &file2).is_ok());
+        assert!(sys::symlink(dir1.mash("link2"), "../link1").is_ok());
+        assert!(sys::write_atomic(dir1.mash("link2"), "this is a link test").is_ok());
+        assert!(link1.is_symlink());
+        assert!(dir1.mash("link2").is_symlink());
+        assert_eq!(sys::readstring(&file2).unwrap(), "this is a link test");
+        assert_eq!(file2.mode().unwrap(), 0o100750);
+        assert_eq!(sys::all_paths(&dir1).unwrap(), vec![dir1.mash("link2")]);
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_write_if_changed() {
+        let tmpdir = setup().mash("file_write_if_changed");
+        let tmpfile = tmpdir.mash("file1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // missing file is always written
+        assert!(sys::write_if_changed(&tmpfile, "this is a test").unwrap());
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "this is a test");
+
+        // identical content is not rewritten
+        assert!(!sys::write_if_changed(&tmpfile, "this is a test").unwrap());
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "this is a test");
+
+        // changed content is written
+        assert!(sys::write_if_changed(&tmpfile, "this is another test").unwrap());
+        assert_eq!(sys::readstring(&tmpfile).unwrap(), "this is another test");
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_write_p() {
         let tmpdir = setup().mash("file_write_p");
@@ -10,6 +10,7 @@ pub use os::*;
 pub use path::*;
 
 // Export modules directly
+pub mod edit;
 pub mod exec;
 pub mod libc;
 pub mod user;
@@ -1,5 +1,12 @@
 use crate::{errors::*, sys};
-use std::io;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::{env, io, mem, path::Path};
+
+lazy_static! {
+    // Matches os-release KEY=VALUE assignments
+    static ref RELEASE_RX: Regex = Regex::new(r"^([A-Za-z0-9_]+)=(.*)$").unwrap();
+}
 
 /// Type of operating system rust is running on
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -59,6 +66,36 @@ pub fn x86_64() -> bool {
     arch() == Arch::X86_64
 }
 
+/// Returns the normalized machine architecture name e.g. `x86_64` or `aarch64` as reported by
+/// `uname` falling back on the compile time architecture if `uname` fails. Aliases like `amd64`
+/// and `arm64` are normalized to `x86_64` and `aarch64` respectively.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(sys::machine().unwrap(), std::env::consts::ARCH);
+/// ```
+pub fn machine() -> FuResult<String> {
+    let mut uts: libc::utsname = unsafe { mem::zeroed() };
+    let machine = match unsafe { libc::uname(&mut uts) } {
+        0 => unsafe { sys::libc::to_string(uts.machine.as_ptr())? },
+        _ => env::consts::ARCH.to_string(),
+    };
+    Ok(normalize_machine(&machine))
+}
+
+// Private helper to normalize the given machine architecture aliases
+fn normalize_machine(machine: &str) -> String {
+    match machine {
+        "amd64" | "x64" | "x86_64" => "x86_64",
+        "i386" | "i486" | "i586" | "i686" | "x86" => "x86",
+        "arm64" | "aarch64" => "aarch64",
+        x => x,
+    }
+    .to_string()
+}
+
 /// Type of operating system rust is running on
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum Platform {
@@ -140,6 +177,114 @@ pub fn info() -> FuResult<Info> {
     Ok(Info { arch: arch(), kernel: version.to_string(), release: release.to_string() })
 }
 
+/// Operating system identification parsed from an os-release file
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct OsRelease {
+    pub id: String,          // Distro identifier e.g. arch
+    pub name: String,        // Distro name e.g. Arch Linux
+    pub version_id: String,  // Distro version e.g. 20.04 or empty for rolling releases
+    pub pretty_name: String, // Distro display name e.g. Arch Linux
+}
+
+/// Get the operating system identification from `/etc/os-release` falling back on
+/// `/usr/lib/os-release` as the os-release spec calls for.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert!(!sys::release().unwrap().id.is_empty());
+/// ```
+pub fn release() -> FuResult<OsRelease> {
+    match Path::new("/etc/os-release").exists() {
+        true => parse_release("/etc/os-release"),
+        false => parse_release("/usr/lib/os-release"),
+    }
+}
+
+/// Parse the given os-release formatted file. Quoted values are unquoted and missing fields use
+/// the defaults of the os-release spec. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("os_doc_parse_release");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let tmpfile = tmpdir.mash("os-release");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::write(&tmpfile, "NAME=\"Arch Linux\"\nID=arch\n").is_ok());
+/// assert_eq!(sys::parse_release(&tmpfile).unwrap().name, "Arch Linux");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn parse_release<T: AsRef<Path>>(path: T) -> FuResult<OsRelease> {
+    let mut release = OsRelease { id: "linux".to_string(), name: "Linux".to_string(), pretty_name: "Linux".to_string(), ..Default::default() };
+    for line in sys::readlines(path)? {
+        if let Some(caps) = RELEASE_RX.captures(line.trim()) {
+            let value = unquote(&caps[2]);
+            match &caps[1] {
+                "ID" => release.id = value,
+                "NAME" => release.name = value,
+                "VERSION_ID" => release.version_id = value,
+                "PRETTY_NAME" => release.pretty_name = value,
+                _ => {},
+            }
+        }
+    }
+    Ok(release)
+}
+
+// Private helper to strip matching single or double quotes from the given value
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    for quote in &["\"", "'"] {
+        if value.len() > 1 && value.starts_with(quote) && value.ends_with(quote) {
+            return value[1..value.len() - 1].to_string();
+        }
+    }
+    value.to_string()
+}
+
+/// Returns the hostname of the system.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert!(!sys::hostname().unwrap().is_empty());
+/// ```
+pub fn hostname() -> FuResult<String> {
+    let size = match unsafe { libc::sysconf(libc::_SC_HOST_NAME_MAX) } {
+        x if x > 0 => x as usize + 1,
+        _ => 256,
+    };
+    let mut buf = vec![0u8; size];
+    if unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) } != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+
+    // Truncated names aren't guaranteed to be NUL terminated
+    let len = buf.iter().position(|x| *x == 0).unwrap_or(buf.len());
+    buf.truncate(len);
+    Ok(String::from_utf8(buf).map_err(|_| StringError::FailedToString)?)
+}
+
+/// Sets the hostname of the system to the given `name`. Requires root privileges.
+///
+/// ### Examples
+/// ```ignore
+/// use fungus::prelude::*;
+///
+/// assert!(sys::set_hostname("foo").is_ok());
+/// ```
+pub fn set_hostname<T: AsRef<str>>(name: T) -> FuResult<()> {
+    let name = name.as_ref();
+    if unsafe { libc::sethostname(name.as_ptr() as *const libc::c_char, name.len()) } != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
 // Substitute stdout and stderr
 pub struct Stdio<T: io::Write, U: io::Write> {
     pub out: T,
@@ -153,6 +298,7 @@ impl<T: io::Write, U: io::Write> Stdio<T, U> {
 
 #[cfg(test)]
 mod tests {
+    use super::normalize_machine;
     use crate::prelude::*;
     use std::io::{self, Write};
 
@@ -161,12 +307,76 @@ mod tests {
         assert!(sys::info().is_ok());
     }
 
+    #[test]
+    fn test_parse_release() {
+        let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("os_parse_release");
+        let file1 = tmpdir.mash("os-release");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // quoted and unquoted values with comments and unknown keys
+        let data = "# comment\nNAME=\"Ubuntu\"\nVERSION=\"20.04.1 LTS (Focal Fossa)\"\nID=ubuntu\n\nPRETTY_NAME='Ubuntu 20.04.1 LTS'\nVERSION_ID=\"20.04\"\n";
+        assert!(sys::write(&file1, data).is_ok());
+        let release = sys::parse_release(&file1).unwrap();
+        assert_eq!(release.id, "ubuntu");
+        assert_eq!(release.name, "Ubuntu");
+        assert_eq!(release.version_id, "20.04");
+        assert_eq!(release.pretty_name, "Ubuntu 20.04.1 LTS");
+
+        // missing fields use the spec defaults
+        assert!(sys::write(&file1, "ID=arch\n").is_ok());
+        let release = sys::parse_release(&file1).unwrap();
+        assert_eq!(release.id, "arch");
+        assert_eq!(release.name, "Linux");
+        assert_eq!(release.version_id, "");
+        assert_eq!(release.pretty_name, "Linux");
+
+        // missing file
+        assert!(sys::parse_release(tmpdir.mash("foo")).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_arch() {
         sys::x86();
         sys::x86_64();
     }
 
+    #[test]
+    fn test_hostname() {
+        let hostname = sys::hostname().unwrap();
+        assert!(!hostname.is_empty());
+        assert!(!hostname.contains('\0'));
+        assert_eq!(hostname, sys::readstring("/proc/sys/kernel/hostname").unwrap().trim());
+    }
+
+    #[test]
+    fn test_set_hostname() {
+        // only test the failure case to avoid changing the hostname
+        if !user::is_root() {
+            let err = sys::set_hostname("foo").unwrap_err();
+            assert_eq!(err.downcast_ref::<io::Error>().unwrap().kind(), io::ErrorKind::PermissionDenied);
+        }
+    }
+
+    #[test]
+    fn test_machine() {
+        let machine = sys::machine().unwrap();
+        assert!(!machine.is_empty());
+        assert_eq!(machine, normalize_machine(std::env::consts::ARCH));
+        assert_eq!(sys::x86_64(), machine == "x86_64");
+
+        // aliases
+        assert_eq!(normalize_machine("amd64"), "x86_64");
+        assert_eq!(normalize_machine("i686"), "x86");
+        assert_eq!(normalize_machine("arm64"), "aarch64");
+        assert_eq!(normalize_machine("armv7l"), "armv7l");
+    }
+
     #[test]
     #[cfg(target_os = "linux")]
     fn test_linux() {
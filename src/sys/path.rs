@@ -5,11 +5,15 @@ use crate::{
 };
 use gory::*;
 use std::{
-    collections::HashMap,
-    ffi::OsStr,
+    collections::{HashMap, HashSet},
+    ffi::{CString, OsStr},
     fs, io,
-    os::unix::fs::{MetadataExt, PermissionsExt},
+    os::unix::{
+        ffi::OsStrExt,
+        fs::{MetadataExt, PermissionsExt},
+    },
     path::{Component, Path, PathBuf},
+    time::SystemTime,
 };
 use walkdir::WalkDir;
 
@@ -60,6 +64,41 @@ pub fn abs<T: AsRef<Path>>(path: T) -> FuResult<PathBuf> {
     Ok(path_buf)
 }
 
+/// Return the path in an absolute form with its parent directory canonicalized i.e. symlinks
+/// resolved while the leaf is kept as is. This is useful when creating a new file under a
+/// symlinked directory as the leaf doesn't need to exist yet but the parent does.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_abs_with_parent");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let dir1 = tmpdir.mash("dir1");
+/// let link1 = tmpdir.mash("link1");
+/// assert!(sys::mkdir(&dir1).is_ok());
+/// assert!(sys::symlink(&link1, &dir1).is_ok());
+/// assert_eq!(sys::abs_with_parent(link1.mash("file1")).unwrap(), dir1.mash("file1"));
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn abs_with_parent<T: AsRef<Path>>(path: T) -> FuResult<PathBuf> {
+    let path = abs(path)?;
+    let dir = fs::canonicalize(path.dir()?)?;
+    Ok(dir.mash(path.base()?))
+}
+
+/// Returns the last access time of the given `path`. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert!(sys::accessed("/etc").unwrap() <= std::time::SystemTime::now());
+/// ```
+pub fn accessed<T: AsRef<Path>>(path: T) -> FuResult<SystemTime> {
+    Ok(metadata(path)?.accessed()?)
+}
+
 /// Returns all directories for the given path recurisely, sorted by filename. Handles path
 /// expansion. Paths are returned as abs paths. Doesn't include the path itself. Paths are
 /// guaranteed to be distinct.
@@ -139,6 +178,54 @@ pub fn all_files<T: AsRef<Path>>(path: T) -> FuResult<Vec<PathBuf>> {
     Err(PathError::does_not_exist(abs).into())
 }
 
+/// Returns all files for the given path recursively, sorted by filename, along with any errors
+/// encountered along the way. Unlike `all_files` entries that can't be read e.g. a directory
+/// without permissions are recorded and skipped rather than aborting the walk. Handles path
+/// expansion. Paths are returned as abs paths and are guaranteed to be distinct.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_all_files_lossy");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::touch(&file1).is_ok());
+/// let (files, errs) = sys::all_files_lossy(&tmpdir).unwrap();
+/// assert_iter_eq(files, vec![file1]);
+/// assert!(errs.is_empty());
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn all_files_lossy<T: AsRef<Path>>(path: T) -> FuResult<(Vec<PathBuf>, Vec<FuError>)> {
+    let abs = path.as_ref().abs()?;
+    if abs.exists() {
+        let mut paths: Vec<PathBuf> = Vec::new();
+        let mut errs: Vec<FuError> = Vec::new();
+        let mut distinct = HashMap::<PathBuf, bool>::new();
+        if abs.is_dir() {
+            for entry in WalkDir::new(&abs).min_depth(1).follow_links(false).sort_by(|x, y| x.file_name().cmp(y.file_name())) {
+                let path = match entry {
+                    Ok(x) => x.into_path(),
+                    Err(e) => {
+                        errs.push(e.into());
+                        continue;
+                    },
+                };
+
+                // Ensure the path is a file and distinct
+                if path.is_file() && !distinct.contains_key(&path) {
+                    distinct.insert(path.clone(), true);
+                    paths.push(path);
+                }
+            }
+            return Ok((paths, errs));
+        }
+        return Err(PathError::is_not_dir(abs).into());
+    }
+    Err(PathError::does_not_exist(abs).into())
+}
+
 /// Returns all paths for the given path recursively, sorted by filename. Handles path
 /// expansion. Paths are returned as abs paths. Doesn't include the path itself. Paths are
 /// guaranteed to be distinct.
@@ -182,6 +269,74 @@ pub fn all_paths<T: AsRef<Path>>(path: T) -> FuResult<Vec<PathBuf>> {
     Err(PathError::does_not_exist(abs).into())
 }
 
+/// Returns the longest common directory shared by all the given `paths`. Handles path expansion
+/// and absolutizes each path before comparing. Returns `/` when nothing but the root is shared
+/// and an error if no paths are given.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let paths = vec!["/usr/local/bin/foo", "/usr/local/lib", "/usr/local/bin"];
+/// assert_eq!(sys::common_root(&paths).unwrap(), PathBuf::from("/usr/local"));
+/// ```
+pub fn common_root<I, T>(paths: I) -> FuResult<PathBuf>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<Path>,
+{
+    let mut root: Option<PathBuf> = None;
+    for path in paths {
+        let path = path.as_ref().abs()?;
+        root = Some(match root {
+            None => path,
+            Some(root) => root.components().zip(path.components()).take_while(|(x, y)| x == y).map(|(x, _)| x).collect(),
+        });
+    }
+    root.ok_or_else(|| PathError::Empty.into())
+}
+
+/// Returns the longest shared directory of the given `paths`. Delegates to `common_root` for
+/// multiple paths while a single path returns its own directory. Returns `/` when nothing but the
+/// root is shared and `PathError::Empty` if no paths are given.
+///
+/// Note a single path is treated as a file and trimmed to its directory, while multiple paths
+/// only share whole components so duplicates of the same path return that path unchanged.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(sys::common_prefix(&["/usr/local/bin/foo", "/usr/local/lib"]).unwrap(), PathBuf::from("/usr/local"));
+/// assert_eq!(sys::common_prefix(&["/usr/local/bin/foo"]).unwrap(), PathBuf::from("/usr/local/bin"));
+/// ```
+pub fn common_prefix<T: AsRef<Path>>(paths: &[T]) -> FuResult<PathBuf> {
+    match paths.len() {
+        0 => Err(PathError::Empty.into()),
+        1 => {
+            // The root has no directory above it so it is its own prefix
+            let path = paths[0].as_ref().abs()?;
+            match path.parent() {
+                Some(dir) => Ok(dir.to_path_buf()),
+                None => Ok(path),
+            }
+        },
+        _ => common_root(paths),
+    }
+}
+
+/// Returns the creation time of the given `path`. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert!(sys::created("/etc").unwrap() <= std::time::SystemTime::now());
+/// ```
+pub fn created<T: AsRef<Path>>(path: T) -> FuResult<SystemTime> {
+    Ok(metadata(path)?.created()?)
+}
+
 /// Returns all directories for the given path, sorted by filename. Handles path expansion.
 /// Paths are returned as abs paths. Doesn't include the path itself only its children nor
 /// is this recursive.
@@ -336,6 +491,41 @@ pub fn files<T: AsRef<Path>>(path: T) -> FuResult<Vec<PathBuf>> {
     Err(PathError::does_not_exist(abs).into())
 }
 
+/// Returns all symlinks found recursively under the given path paired with their raw link
+/// targets, sorted by filename. Handles path expansion. Links are not followed and broken links
+/// are included. Link paths are returned as abs paths while targets are returned as is.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_find_symlinks");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let link1 = tmpdir.mash("link1");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::symlink(&link1, "file1").is_ok());
+/// assert_iter_eq(sys::find_symlinks(&tmpdir).unwrap(), vec![(link1, PathBuf::from("file1"))]);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn find_symlinks<T: AsRef<Path>>(path: T) -> FuResult<Vec<(PathBuf, PathBuf)>> {
+    let abs = path.as_ref().abs()?;
+    if abs.exists() {
+        if abs.is_dir() {
+            let mut links: Vec<(PathBuf, PathBuf)> = Vec::new();
+            for entry in WalkDir::new(&abs).min_depth(1).follow_links(false).sort_by(|x, y| x.file_name().cmp(y.file_name())) {
+                let entry = entry?;
+                if entry.path_is_symlink() {
+                    let target = fs::read_link(entry.path())?;
+                    links.push((entry.into_path(), target));
+                }
+            }
+            return Ok(links);
+        }
+        return Err(PathError::is_not_dir(abs).into());
+    }
+    Err(PathError::does_not_exist(abs).into())
+}
+
 /// Returns true if the given path exists and is a directory. Handles path expansion.
 ///
 /// ### Examples
@@ -351,6 +541,26 @@ pub fn is_dir<T: AsRef<Path>>(path: T) -> bool {
     }
 }
 
+/// Returns true if the given path is a zero length file or a directory without any entries. Returns
+/// false otherwise including for paths that don't exist. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(sys::is_empty("/etc"), false);
+/// ```
+pub fn is_empty<T: AsRef<Path>>(path: T) -> bool {
+    match metadata(&path) {
+        Ok(x) if x.is_file() => x.len() == 0,
+        Ok(x) if x.is_dir() => match path.as_ref().abs().and_then(|x| Ok(fs::read_dir(x)?)) {
+            Ok(mut entries) => entries.next().is_none(),
+            Err(_) => false,
+        },
+        _ => false,
+    }
+}
+
 /// Returns true if the given path exists and is an executable. Handles path expansion
 ///
 /// ### Examples
@@ -390,6 +600,20 @@ pub fn is_file<T: AsRef<Path>>(path: T) -> bool {
     }
 }
 
+/// Returns true if the current process has read access to the given path. Unlike inspecting
+/// mode bits this takes ownership and privileges into account. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(sys::is_readable("/etc/hosts"), true);
+/// assert_eq!(sys::is_readable("/foo/bar"), false);
+/// ```
+pub fn is_readable<T: AsRef<Path>>(path: T) -> bool {
+    access(path, libc::R_OK)
+}
+
 /// Returns true if the given path exists and is readonly. Handles path expansion
 ///
 /// ### Examples
@@ -496,6 +720,38 @@ pub fn is_symlink_file<T: AsRef<Path>>(path: T) -> bool {
     }
 }
 
+/// Returns true if the current process has write access to the given path. Unlike inspecting
+/// mode bits this takes ownership and privileges into account. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_is_writable");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1");
+/// assert_eq!(sys::is_writable(&file1), false);
+/// assert!(sys::touch_p(&file1, 0o644).is_ok());
+/// assert_eq!(sys::is_writable(&file1), true);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn is_writable<T: AsRef<Path>>(path: T) -> bool {
+    access(path, libc::W_OK)
+}
+
+// Private helper to check the given access mode for the current process with `libc::access`
+fn access<T: AsRef<Path>>(path: T, mode: libc::c_int) -> bool {
+    let path = match path.as_ref().abs() {
+        Ok(x) => x,
+        Err(_) => return false,
+    };
+    match CString::new(path.as_os_str().as_bytes()) {
+        Ok(x) => unsafe { libc::access(x.as_ptr(), mode) == 0 },
+        Err(_) => false,
+    }
+}
+
 /// Returns the group ID of the owner of this file. Handles path expansion.
 ///
 /// ### Examples
@@ -511,6 +767,10 @@ pub fn gid<T: AsRef<Path>>(path: T) -> FuResult<u32> {
 /// Returns a vector of all paths from the given target glob with path expansion and sorted by
 /// name. Doesn't include the target itself only its children nor is this recursive.
 ///
+/// Brace expressions are expanded before globbing e.g. `foo.{rs,toml}` matches both `foo.rs` and
+/// `foo.toml` with the results of each alternative returned in order. Braces may be nested while
+/// empty `{}` or single item `{a}` braces are treated literally.
+///
 /// ### Examples
 /// ```
 /// use fungus::prelude::*;
@@ -523,18 +783,70 @@ pub fn gid<T: AsRef<Path>>(path: T) -> FuResult<u32> {
 /// assert!(sys::mkdir(&dir1).is_ok());
 /// assert!(sys::mkdir(&dir2).is_ok());
 /// assert!(sys::touch(&file1).is_ok());
-/// assert_iter_eq(sys::glob(tmpdir.mash("*")).unwrap(), vec![dir1, dir2, file1]);
+/// assert_iter_eq(sys::glob(tmpdir.mash("*")).unwrap(), vec![dir1.clone(), dir2, file1.clone()]);
+/// assert_iter_eq(sys::glob(tmpdir.mash("{file,dir}1")).unwrap(), vec![file1, dir1]);
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// ```
 pub fn glob<T: AsRef<Path>>(src: T) -> FuResult<Vec<PathBuf>> {
-    let abs = src.as_ref().abs()?.to_string()?;
+    let mut seen = HashSet::new();
     let mut paths: Vec<PathBuf> = Vec::new();
-    for x in glob::glob(&abs)? {
-        paths.push(x.map_err(|err| io::Error::new(io::ErrorKind::Other, format!("glob failure: {}", err.to_string())))?.abs()?);
+    for pattern in expand_braces(&src.as_ref().to_string()?) {
+        let abs = PathBuf::from(pattern).abs()?.to_string()?;
+        for x in glob::glob(&abs)? {
+            let path = x.map_err(|err| io::Error::new(io::ErrorKind::Other, format!("glob failure: {}", err.to_string())))?.abs()?;
+            if seen.insert(path.clone()) {
+                paths.push(path);
+            }
+        }
     }
     Ok(paths)
 }
 
+// Private helper to expand brace expressions e.g. `a{b,c{d,e}}` into `ab`, `acd` and `ace`.
+// Unbalanced braces and braces without a top level comma are left as is.
+fn expand_braces(pattern: &str) -> Vec<String> {
+    let chars: Vec<char> = pattern.chars().collect();
+    for open in (0..chars.len()).filter(|&i| chars[i] == '{') {
+        // Find the matching close brace and the top level commas
+        let mut depth = 0;
+        let mut commas = vec![];
+        let mut close = None;
+        for (i, c) in chars.iter().enumerate().skip(open) {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        close = Some(i);
+                        break;
+                    }
+                },
+                ',' if depth == 1 => commas.push(i),
+                _ => (),
+            }
+        }
+
+        // Expand each alternative recursively to handle nested and later braces
+        if let Some(close) = close {
+            if !commas.is_empty() {
+                let prefix: String = chars[..open].iter().collect();
+                let suffix: String = chars[close + 1..].iter().collect();
+                let mut bounds = vec![open];
+                bounds.extend(commas);
+                bounds.push(close);
+                return bounds
+                    .windows(2)
+                    .flat_map(|x| {
+                        let alt: String = chars[x[0] + 1..x[1]].iter().collect();
+                        expand_braces(&format!("{}{}{}", prefix, alt, suffix))
+                    })
+                    .collect();
+            }
+        }
+    }
+    vec![pattern.to_string()]
+}
+
 /// Returns a new owned [`PathBuf`] from `dir` mashed together with `base`.
 /// Differs from the `join` implementation as `mash` drops root prefix of the given `path` if
 /// it exists and also drops any trailing '/' on the new resulting path. More closely aligns
@@ -566,6 +878,18 @@ pub fn metadata<T: AsRef<Path>>(path: T) -> FuResult<fs::Metadata> {
     Ok(meta)
 }
 
+/// Returns the last modification time of the given `path`. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert!(sys::modified("/etc").unwrap() <= std::time::SystemTime::now());
+/// ```
+pub fn modified<T: AsRef<Path>>(path: T) -> FuResult<SystemTime> {
+    Ok(metadata(path)?.modified()?)
+}
+
 /// Parse unix shell pathing e.g. $PATH, $XDG_DATA_DIRS or $XDG_CONFIG_DIRS.
 /// List of directories seperated by :
 ///
@@ -609,14 +933,42 @@ pub fn parse_paths<T: AsRef<str>>(value: T) -> FuResult<Vec<PathBuf>> {
 /// assert!(sys::remove_all(&tmpdir).is_ok());
 /// ```
 pub fn paths<T: AsRef<Path>>(path: T) -> FuResult<Vec<PathBuf>> {
+    read_dir_filtered(path, |_| true)
+}
+
+/// Returns all directories/files for the given path for which the predicate `f` returns true,
+/// sorted by filename. Handles path expansion. Paths are returned as abs paths. Doesn't include
+/// the path itself only its children nor is this recursive.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_read_dir_filtered");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// let file1 = tmpdir.mash("file1.rs");
+/// let file2 = tmpdir.mash("file2.toml");
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::touch(&file1).is_ok());
+/// assert!(sys::touch(&file2).is_ok());
+/// assert_iter_eq(sys::read_dir_filtered(&tmpdir, |x| x.has_suffix(".rs")).unwrap(), vec![file1]);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn read_dir_filtered<T, F>(path: T, f: F) -> FuResult<Vec<PathBuf>>
+where
+    T: AsRef<Path>,
+    F: Fn(&Path) -> bool,
+{
     let abs = path.as_ref().abs()?;
     if abs.exists() {
         if abs.is_dir() {
             let mut paths: Vec<PathBuf> = Vec::new();
             for entry in fs::read_dir(abs)? {
                 let entry = entry?;
-                let path = entry.path();
-                paths.push(path.abs()?);
+                let path = entry.path().abs()?;
+                if f(&path) {
+                    paths.push(path);
+                }
             }
             paths.sort();
             return Ok(paths);
@@ -675,6 +1027,171 @@ pub fn rel_to(dir: &str) -> FuResult<PathBuf> {
     Ok(path)
 }
 
+/// Returns the given `path` relative to the given `base`. Delegates to `PathExt::relative_from`
+/// for symmetry with the other free functions.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(sys::relpath("foo/bar1", "foo/bar2").unwrap(), PathBuf::from("bar1"));
+/// ```
+pub fn relpath<T: AsRef<Path>, U: AsRef<Path>>(path: T, base: U) -> FuResult<PathBuf> {
+    path.as_ref().relative_from(base)
+}
+
+/// Returns the size in bytes of the given `path`. Files return their length while directories
+/// are walked recursively summing the sizes of all regular files without following links.
+/// Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_size");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(&tmpdir).is_ok());
+/// assert!(sys::write(tmpdir.mash("file1"), "foobar").is_ok());
+/// assert_eq!(sys::size(&tmpdir).unwrap(), 6);
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn size<T: AsRef<Path>>(path: T) -> FuResult<u64> {
+    let path = path.as_ref().abs()?;
+    let meta = fs::metadata(&path)?;
+    if !meta.is_dir() {
+        return Ok(meta.len());
+    }
+    let mut size = 0;
+    for entry in WalkDir::new(&path).follow_links(false) {
+        let entry = entry?;
+        if entry.file_type().is_file() {
+            size += entry.metadata()?.len();
+        }
+    }
+    Ok(size)
+}
+
+/// Tree provides options for rendering a textual directory listing similar to the `tree` command.
+#[derive(Debug, Clone)]
+pub struct Tree {
+    path: PathBuf,            // root path to render
+    color: bool,              // colorize directories and links
+    max_depth: Option<usize>, // maximum depth to descend
+}
+
+impl Tree {
+    /// Update the `color` option. Default is disabled.
+    /// When `yes` is `true`, directories are rendered in blue and links in cyan.
+    pub fn color(&mut self, yes: bool) -> &mut Self {
+        self.color = yes;
+        self
+    }
+
+    /// Update the `max_depth` option. Default is unlimited.
+    /// Only entries up to the given `depth` below the root are rendered, `1` being the root's
+    /// immediate children.
+    pub fn max_depth(&mut self, depth: usize) -> &mut Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Render the tree with the current options.
+    pub fn render(&self) -> FuResult<String> {
+        let abs = self.path.abs()?;
+        if !abs.is_dir() {
+            return Err(PathError::is_not_dir(&abs).into());
+        }
+        let mut out = match self.color {
+            true => format!("{}\n", self.path.blue()),
+            false => format!("{}\n", self.path.display()),
+        };
+        self.render_dir(&abs, "", 1, &mut out)?;
+        Ok(out)
+    }
+
+    // Render the entries of the given directory, directories first then files each sorted by name
+    fn render_dir(&self, dir: &Path, prefix: &str, depth: usize, out: &mut String) -> FuResult<()> {
+        if let Some(max) = self.max_depth {
+            if depth > max {
+                return Ok(());
+            }
+        }
+
+        let mut dirs = vec![];
+        let mut files = vec![];
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            match entry.file_type()?.is_dir() {
+                true => dirs.push(entry.path()),
+                false => files.push(entry.path()),
+            }
+        }
+        dirs.sort();
+        files.sort();
+
+        let count = dirs.len() + files.len();
+        for (i, path) in dirs.iter().chain(files.iter()).enumerate() {
+            let last = i + 1 == count;
+            let name = PathBuf::from(path.base()?);
+            let label = match path {
+                x if x.is_symlink() => {
+                    let target = x.readlink()?;
+                    match self.color {
+                        true => format!("{} -> {}", name.cyan(), target.display()),
+                        false => format!("{} -> {}", name.display(), target.display()),
+                    }
+                },
+                x if i < dirs.len() && self.color => format!("{}", x.base()?.blue()),
+                _ => name.display().to_string(),
+            };
+            out.push_str(&format!("{}{}{}\n", prefix, if last { "└── " } else { "├── " }, label));
+            if i < dirs.len() {
+                self.render_dir(path, &format!("{}{}", prefix, if last { "    " } else { "│   " }), depth + 1, out)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Wraps `tree_p` to render the given directory as an indented tree using `├──`, `└──` and `│`
+/// connectors with directories listed before files and each level sorted by name. Links are
+/// rendered with their targets but not followed. Handles path expansion.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_tree");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(tmpdir.mash("dir1")).is_ok());
+/// assert!(sys::touch(tmpdir.mash("dir1/file2")).is_ok());
+/// assert!(sys::touch(tmpdir.mash("file1")).is_ok());
+/// assert_eq!(sys::tree("tests/temp/path_doc_tree").unwrap(), "tests/temp/path_doc_tree\n├── dir1\n│   └── file2\n└── file1\n");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn tree<T: AsRef<Path>>(path: T) -> FuResult<String> {
+    tree_p(path).render()
+}
+
+/// Create [`Tree`] options for rendering the given directory. This function provides more control
+/// over options than the `tree` function. Nothing is rendered until the `render` method is called.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("path_doc_tree_p");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// assert!(sys::mkdir(tmpdir.mash("dir1")).is_ok());
+/// assert!(sys::touch(tmpdir.mash("dir1/file2")).is_ok());
+/// assert!(sys::touch(tmpdir.mash("file1")).is_ok());
+/// assert_eq!(sys::tree_p("tests/temp/path_doc_tree_p").max_depth(1).render().unwrap(), "tests/temp/path_doc_tree_p\n├── dir1\n└── file1\n");
+/// assert!(sys::remove_all(&tmpdir).is_ok());
+/// ```
+pub fn tree_p<T: AsRef<Path>>(path: T) -> Tree {
+    Tree { path: path.as_ref().to_path_buf(), color: false, max_depth: None }
+}
+
 /// Returns the user ID of the owner of this file. Handles path expansion.
 ///
 /// ### Examples
@@ -713,6 +1230,16 @@ pub trait PathExt {
     /// ```
     fn abs_from<T: AsRef<Path>>(&self, path: T) -> FuResult<PathBuf>;
 
+    /// Returns the last access time of the `Path`.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert!(Path::new("/etc").accessed().unwrap() <= std::time::SystemTime::now());
+    /// ```
+    fn accessed(&self) -> FuResult<SystemTime>;
+
     /// Returns the final component of the `Path`, if there is one.
     ///
     /// ### Examples
@@ -769,6 +1296,16 @@ pub trait PathExt {
     /// ```
     fn concat<T: AsRef<str>>(&self, val: T) -> FuResult<PathBuf>;
 
+    /// Returns the creation time of the `Path`.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert!(Path::new("/etc").created().unwrap() <= std::time::SystemTime::now());
+    /// ```
+    fn created(&self) -> FuResult<SystemTime>;
+
     /// Returns the `Path` without its final component, if there is one.
     ///
     /// ### Examples
@@ -855,6 +1392,38 @@ pub trait PathExt {
     /// ```
     fn has<T: AsRef<Path>>(&self, path: T) -> bool;
 
+    /// Returns true if the `Path` contains all of the given paths or strings. Stops checking at
+    /// the first needle not found.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let path = PathBuf::from("/foo/bar");
+    /// assert_eq!(path.has_all(vec!["foo", "bar"]), true);
+    /// assert_eq!(path.has_all(vec!["foo", "blah"]), false);
+    /// ```
+    fn has_all<I, T>(&self, needles: I) -> bool
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<Path>;
+
+    /// Returns true if the `Path` contains any of the given paths or strings. Stops checking at
+    /// the first needle found.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let path = PathBuf::from("/foo/bar");
+    /// assert_eq!(path.has_any(vec!["blah", "bar"]), true);
+    /// assert_eq!(path.has_any(vec!["blah", "baz"]), false);
+    /// ```
+    fn has_any<I, T>(&self, needles: I) -> bool
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<Path>;
+
     /// Returns true if the `Path` as a String has the given prefix
     ///
     /// ### Examples
@@ -879,6 +1448,20 @@ pub trait PathExt {
     /// ```
     fn has_suffix<T: AsRef<Path>>(&self, suffix: T) -> bool;
 
+    /// Returns true if the `Path` is absolute and already clean i.e. identical to its own
+    /// `clean` output with no `.` or `..` components, repeated separators or trailing separator.
+    /// Doesn't touch the filesystem.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!(Path::new("/foo/bar").is_abs_clean(), true);
+    /// assert_eq!(Path::new("/foo/./bar").is_abs_clean(), false);
+    /// assert_eq!(Path::new("foo/bar").is_abs_clean(), false);
+    /// ```
+    fn is_abs_clean(&self) -> bool;
+
     /// Returns true if the `Path` exists and is a directory. Handles path expansion.
     ///
     /// ### Examples
@@ -889,6 +1472,17 @@ pub trait PathExt {
     /// ```
     fn is_dir(&self) -> bool;
 
+    /// Returns true if the `Path` is a zero length file or a directory without any entries.
+    /// Handles path expansion.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!(Path::new("/etc").is_empty(), false);
+    /// ```
+    fn is_empty(&self) -> bool;
+
     /// Returns true if the `Path` exists and is an executable. Handles path expansion.
     ///
     /// ### Examples
@@ -918,6 +1512,16 @@ pub trait PathExt {
     /// ```
     fn is_file(&self) -> bool;
 
+    /// Returns true if the current process has read access to the `Path`. Handles path expansion.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!(Path::new("/etc/hosts").is_readable(), true);
+    /// ```
+    fn is_readable(&self) -> bool;
+
     /// Returns true if the `Path` exists and is readonly. Handles path expansion.
     ///
     /// ### Examples
@@ -991,6 +1595,22 @@ pub trait PathExt {
     /// ```
     fn is_symlink_file(&self) -> bool;
 
+    /// Returns true if the current process has write access to the `Path`. Handles path expansion.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("doc_is_writable");
+    /// assert!(sys::remove_all(&tmpdir).is_ok());
+    /// assert!(sys::mkdir(&tmpdir).is_ok());
+    /// let file1 = tmpdir.mash("file1");
+    /// assert!(sys::touch_p(&file1, 0o644).is_ok());
+    /// assert_eq!(file1.is_writable(), true);
+    /// assert!(sys::remove_all(&tmpdir).is_ok());
+    /// ```
+    fn is_writable(&self) -> bool;
+
     /// Returns the last path component.
     ///
     /// ### Examples
@@ -1044,6 +1664,16 @@ pub trait PathExt {
     /// ```
     fn mode(&self) -> FuResult<u32>;
 
+    /// Returns the last modification time of the `Path`.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert!(Path::new("/etc").modified().unwrap() <= std::time::SystemTime::now());
+    /// ```
+    fn modified(&self) -> FuResult<SystemTime>;
+
     /// Returns the final component of the `Path` without an extension if there is one
     ///
     /// ### Examples
@@ -1087,17 +1717,39 @@ pub trait PathExt {
     /// assert_eq!(link1.readlink().unwrap(), file1);
     /// assert!(sys::remove_all(&tmpdir).is_ok());
     /// ```
-    fn readlink(&self) -> FuResult<PathBuf>;
+    fn readlink(&self) -> FuResult<PathBuf>;
+
+    /// Returns the `Path` relative to the given `Path`
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!(PathBuf::from("foo/bar1").relative_from("foo/bar2").unwrap(), PathBuf::from("bar1"));
+    /// ```
+    fn relative_from<T: AsRef<Path>>(&self, path: T) -> FuResult<PathBuf>;
 
-    /// Returns the `Path` relative to the given `Path`
+    /// Returns the final absolute target of the `Path` by following its chain of symlinks until a
+    /// non-link is reached. Relative link targets are resolved relative to the link's directory.
+    /// Returns a [`PathError::SymlinkLoop`] if the chain loops or is more than 40 links deep.
     ///
     /// ### Examples
     /// ```
     /// use fungus::prelude::*;
     ///
-    /// assert_eq!(PathBuf::from("foo/bar1").relative_from("foo/bar2").unwrap(), PathBuf::from("bar1"));
+    /// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("pathbuf_doc_resolve");
+    /// assert!(sys::remove_all(&tmpdir).is_ok());
+    /// let file1 = tmpdir.mash("file1");
+    /// let link1 = tmpdir.mash("link1");
+    /// let link2 = tmpdir.mash("link2");
+    /// assert!(sys::mkdir(&tmpdir).is_ok());
+    /// assert!(sys::touch(&file1).is_ok());
+    /// assert!(sys::symlink(&link1, "file1").is_ok());
+    /// assert!(sys::symlink(&link2, "link1").is_ok());
+    /// assert_eq!(link2.resolve().unwrap(), file1);
+    /// assert!(sys::remove_all(&tmpdir).is_ok());
     /// ```
-    fn relative_from<T: AsRef<Path>>(&self, path: T) -> FuResult<PathBuf>;
+    fn resolve(&self) -> FuResult<PathBuf>;
 
     /// Set the given [`Permissions`] on the `Path` and return the `Path`
     ///
@@ -1118,6 +1770,23 @@ pub trait PathExt {
     /// ```
     fn setperms(&self, perms: fs::Permissions) -> FuResult<PathBuf>;
 
+    /// Returns the size in bytes of the `Path`. Directories are walked recursively summing the
+    /// sizes of all regular files without following links.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("pathbuf_doc_size");
+    /// assert!(sys::remove_all(&tmpdir).is_ok());
+    /// let file1 = tmpdir.mash("file1");
+    /// assert!(sys::mkdir(&tmpdir).is_ok());
+    /// assert!(sys::write(&file1, "foobar").is_ok());
+    /// assert_eq!(file1.size().unwrap(), 6);
+    /// assert!(sys::remove_all(&tmpdir).is_ok());
+    /// ```
+    fn size(&self) -> FuResult<u64>;
+
     /// Returns a new [`PathBuf`] with the file extension trimmed off.
     ///
     /// ### Examples
@@ -1188,6 +1857,18 @@ pub trait PathExt {
     /// assert_eq!(Path::new("/etc").uid().unwrap(), 0);
     /// ```
     fn uid(&self) -> FuResult<u32>;
+
+    /// Returns a new [`PathBuf`] with any existing extension replaced by the given `ext`. A
+    /// leading dot in `ext` is optional and a path without an extension simply gains one.
+    ///
+    /// ### Examples
+    /// ```
+    /// use fungus::prelude::*;
+    ///
+    /// assert_eq!(Path::new("foo.md").with_ext("html").unwrap(), PathBuf::from("foo.html"));
+    /// assert_eq!(Path::new("foo").with_ext(".html").unwrap(), PathBuf::from("foo.html"));
+    /// ```
+    fn with_ext<T: AsRef<str>>(&self, ext: T) -> FuResult<PathBuf>;
 }
 
 impl PathExt for Path {
@@ -1214,6 +1895,10 @@ impl PathExt for Path {
         Ok(self.to_path_buf())
     }
 
+    fn accessed(&self) -> FuResult<SystemTime> {
+        Ok(self.metadata()?.accessed()?)
+    }
+
     fn base(&self) -> FuResult<String> {
         self.file_name().ok_or_else(|| PathError::filename_not_found(self))?.to_string()
     }
@@ -1275,6 +1960,10 @@ impl PathExt for Path {
         Ok(PathBuf::from(format!("{}{}", self.to_string()?, val.as_ref())))
     }
 
+    fn created(&self) -> FuResult<SystemTime> {
+        Ok(self.metadata()?.created()?)
+    }
+
     fn dir(&self) -> FuResult<PathBuf> {
         let dir = self.parent().ok_or_else(|| PathError::parent_not_found(self))?;
         Ok(dir.to_path_buf())
@@ -1314,6 +2003,22 @@ impl PathExt for Path {
         }
     }
 
+    fn has_all<I, T>(&self, needles: I) -> bool
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<Path>,
+    {
+        needles.into_iter().all(|x| self.has(x))
+    }
+
+    fn has_any<I, T>(&self, needles: I) -> bool
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<Path>,
+    {
+        needles.into_iter().any(|x| self.has(x))
+    }
+
     fn has_prefix<T: AsRef<Path>>(&self, prefix: T) -> bool {
         match (self.to_string(), prefix.as_ref().to_string()) {
             (Ok(base), Ok(prefix)) => base.starts_with(&prefix),
@@ -1328,10 +2033,21 @@ impl PathExt for Path {
         }
     }
 
+    fn is_abs_clean(&self) -> bool {
+        match self.clean() {
+            Ok(x) => self.is_absolute() && x.as_os_str() == self.as_os_str(),
+            Err(_) => false,
+        }
+    }
+
     fn is_dir(&self) -> bool {
         is_dir(self)
     }
 
+    fn is_empty(&self) -> bool {
+        is_empty(self)
+    }
+
     fn is_exec(&self) -> bool {
         is_exec(self)
     }
@@ -1340,6 +2056,10 @@ impl PathExt for Path {
         is_file(self)
     }
 
+    fn is_readable(&self) -> bool {
+        is_readable(self)
+    }
+
     fn is_readonly(&self) -> bool {
         is_readonly(self)
     }
@@ -1356,6 +2076,10 @@ impl PathExt for Path {
         is_symlink_file(self)
     }
 
+    fn is_writable(&self) -> bool {
+        is_writable(self)
+    }
+
     fn last(&self) -> FuResult<Component> {
         self.components().last_result()
     }
@@ -1374,6 +2098,10 @@ impl PathExt for Path {
         Ok(perms.mode())
     }
 
+    fn modified(&self) -> FuResult<SystemTime> {
+        Ok(self.metadata()?.modified()?)
+    }
+
     fn name(&self) -> FuResult<String> {
         self.trim_ext()?.base()
     }
@@ -1420,11 +2148,27 @@ impl PathExt for Path {
         Ok(path)
     }
 
+    fn resolve(&self) -> FuResult<PathBuf> {
+        let mut path = self.abs()?;
+        let mut seen = HashSet::new();
+        while path.is_symlink() {
+            if seen.len() >= 40 || !seen.insert(path.clone()) {
+                return Err(PathError::symlink_loop(self).into());
+            }
+            path = path.readlink()?.abs_from(&path)?;
+        }
+        Ok(path)
+    }
+
     fn setperms(&self, perms: fs::Permissions) -> FuResult<PathBuf> {
         fs::set_permissions(&self, perms)?;
         Ok(self.to_path_buf())
     }
 
+    fn size(&self) -> FuResult<u64> {
+        size(self)
+    }
+
     fn trim_ext(&self) -> FuResult<PathBuf> {
         Ok(match self.extension() {
             Some(val) => self.trim_suffix(format!(".{}", val.to_string()?)),
@@ -1479,6 +2223,14 @@ impl PathExt for Path {
     fn uid(&self) -> FuResult<u32> {
         uid(&self)
     }
+
+    fn with_ext<T: AsRef<str>>(&self, ext: T) -> FuResult<PathBuf> {
+        let path = self.trim_ext()?;
+        match ext.as_ref().trim_start_matches('.') {
+            "" => Ok(path),
+            x => path.concat(format!(".{}", x)),
+        }
+    }
 }
 
 pub trait PathColorExt {
@@ -1529,8 +2281,12 @@ impl PathColorExt for Path {
 // -------------------------------------------------------------------------------------------------
 #[cfg(test)]
 mod tests {
+    use super::expand_braces;
     use crate::prelude::*;
-    use std::path::Component;
+    use std::{
+        path::Component,
+        time::{Duration, SystemTime},
+    };
 
     // Test setup
     fn setup() -> PathBuf {
@@ -1578,6 +2334,38 @@ mod tests {
         // assert!(sys::abs("../../../../../foo").is_err());
     }
 
+    #[test]
+    fn test_abs_with_parent() {
+        let tmpdir = setup().mash("path_abs_with_parent");
+        let dir1 = tmpdir.mash("dir1");
+        let link1 = tmpdir.mash("link1");
+        let file1 = link1.mash("file1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::symlink(&link1, &dir1).is_ok());
+
+        // parent resolved while leaf doesn't exist yet
+        let target = sys::abs_with_parent(&file1).unwrap();
+        assert_eq!(target, fs::canonicalize(&dir1).unwrap().mash("file1"));
+        assert!(sys::write(&target, "this is a test").is_ok());
+        assert!(dir1.mash("file1").is_file());
+        assert!(!dir1.mash("file1").is_symlink());
+        assert_eq!(sys::readstring(&file1).unwrap(), "this is a test");
+
+        // leaf symlinks are not resolved
+        let link2 = dir1.mash("link2");
+        assert!(sys::symlink(&link2, dir1.mash("file1")).is_ok());
+        assert_eq!(sys::abs_with_parent(link1.mash("link2")).unwrap(), fs::canonicalize(&dir1).unwrap().mash("link2"));
+
+        // parent must exist
+        assert!(sys::abs_with_parent(tmpdir.mash("foo/file1")).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_all_dirs() {
         let tmpdir = setup().mash("path_all_dirs");
@@ -1654,6 +2442,48 @@ mod tests {
         assert_eq!(tmpdir.exists(), false);
     }
 
+    #[test]
+    fn test_all_files_lossy() {
+        let tmpdir = setup().mash("path_all_files_lossy");
+        let dir1 = tmpdir.mash("dir1");
+        let dir2 = tmpdir.mash("dir2");
+        let file1 = dir1.mash("file1");
+        let file2 = dir2.mash("file2");
+        let file3 = tmpdir.mash("file3");
+
+        // invalid target
+        assert!(sys::all_files_lossy("").is_err());
+        assert!(sys::all_files_lossy("foobar").is_err());
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::mkdir(&dir2).is_ok());
+        assert!(sys::touch(&file1).is_ok());
+        assert!(sys::touch(&file2).is_ok());
+        assert!(sys::touch(&file3).is_ok());
+        assert!(sys::all_files_lossy(&file3).is_err());
+
+        // all readable
+        let (files, errs) = sys::all_files_lossy(&tmpdir).unwrap();
+        assert_iter_eq(files, vec![file1.clone(), file2, file3.clone()]);
+        assert!(errs.is_empty());
+
+        // root can read regardless of permissions
+        if !user::is_root() {
+            assert!(sys::chmod(&dir2, 0o000).is_ok());
+            assert!(sys::all_files(&tmpdir).is_err());
+            let (files, errs) = sys::all_files_lossy(&tmpdir).unwrap();
+            assert_iter_eq(files, vec![file1, file3]);
+            assert_eq!(errs.len(), 1);
+            assert!(errs[0].is::<walkdir::Error>());
+            assert!(sys::chmod(&dir2, 0o755).is_ok());
+        }
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_all_paths() {
         let tmpdir = setup().mash("path_all_paths");
@@ -1692,6 +2522,53 @@ mod tests {
         assert_eq!(tmpdir.exists(), false);
     }
 
+    #[test]
+    fn test_common_root() {
+        // deep ancestor
+        let paths = vec!["/usr/local/bin/foo", "/usr/local/bin/bar", "/usr/local/bin/baz/blah"];
+        assert_eq!(sys::common_root(&paths).unwrap(), PathBuf::from("/usr/local/bin"));
+        let paths = vec![PathBuf::from("/usr/local/bin"), PathBuf::from("/usr/local/lib/"), PathBuf::from("/usr/local/./share")];
+        assert_eq!(sys::common_root(&paths).unwrap(), PathBuf::from("/usr/local"));
+
+        // single path is its own root
+        assert_eq!(sys::common_root(vec!["/usr/local/bin"]).unwrap(), PathBuf::from("/usr/local/bin"));
+
+        // only root is shared
+        assert_eq!(sys::common_root(vec!["/usr/local/bin", "/etc/foo"]).unwrap(), PathBuf::from("/"));
+
+        // relative paths are absolutized
+        let cwd = sys::cwd().unwrap();
+        assert_eq!(sys::common_root(vec!["foo/bar", "foo/baz"]).unwrap(), cwd.mash("foo"));
+
+        // empty input
+        let paths: Vec<PathBuf> = vec![];
+        assert_eq!(sys::common_root(&paths).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::Empty));
+    }
+
+    #[test]
+    fn test_common_prefix() {
+        // shared grandparent
+        let paths = vec!["/usr/local/bin/foo", "/usr/local/lib/bar"];
+        assert_eq!(sys::common_prefix(&paths).unwrap(), PathBuf::from("/usr/local"));
+
+        // only root is shared
+        assert_eq!(sys::common_prefix(&["/usr/local/bin", "/etc/foo"]).unwrap(), PathBuf::from("/"));
+
+        // single path returns its directory
+        assert_eq!(sys::common_prefix(&[PathBuf::from("/usr/local/bin/foo")]).unwrap(), PathBuf::from("/usr/local/bin"));
+
+        // root is its own prefix
+        assert_eq!(sys::common_prefix(&["/"]).unwrap(), PathBuf::from("/"));
+        assert_eq!(sys::common_prefix(&["/", "/usr"]).unwrap(), PathBuf::from("/"));
+
+        // duplicate paths share all their components
+        assert_eq!(sys::common_prefix(&["/usr/local/bin", "/usr/local/bin"]).unwrap(), PathBuf::from("/usr/local/bin"));
+
+        // empty input
+        let paths: Vec<PathBuf> = vec![];
+        assert_eq!(sys::common_prefix(&paths).unwrap_err().downcast_ref::<PathError>(), Some(&PathError::Empty));
+    }
+
     #[test]
     fn test_dirs() {
         let tmpdir = setup().mash("path_dirs");
@@ -1780,11 +2657,121 @@ mod tests {
         assert_eq!(tmpdir.exists(), false);
     }
 
+    #[test]
+    fn test_find_symlinks() {
+        let tmpdir = setup().mash("path_find_symlinks");
+        let dir1 = tmpdir.mash("dir1");
+        let file1 = tmpdir.mash("file1");
+        let link1 = dir1.mash("link1");
+        let link2 = tmpdir.mash("link2");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::touch(&file1).is_ok());
+        assert!(sys::symlink(&link1, "../file1").is_ok());
+        assert!(sys::symlink(&link2, "dangling").is_ok());
+
+        // valid and dangling links are both reported with raw targets
+        let links = vec![(link1, PathBuf::from("../file1")), (link2, PathBuf::from("dangling"))];
+        assert_iter_eq(sys::find_symlinks(&tmpdir).unwrap(), links);
+
+        // errors
+        assert!(sys::find_symlinks(&file1).is_err());
+        assert!(sys::find_symlinks(tmpdir.mash("foo")).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_rel_to() {
         assert_eq!(sys::rel_to("home").unwrap(), PathBuf::from("/home"));
     }
 
+    #[test]
+    fn test_relpath() {
+        assert_eq!(sys::relpath("foo/bar1", "foo/bar2").unwrap(), PathBuf::from("bar1"));
+        assert_eq!(sys::relpath("blah1/foo1/bar1", "blah2/foo2/bar2").unwrap(), PathBuf::from("../../blah1/foo1/bar1"));
+        assert_eq!(sys::relpath("blah1/foo1/bar1", "blah2/foo2/bar2").unwrap(), PathBuf::from("blah1/foo1/bar1").relative_from("blah2/foo2/bar2").unwrap());
+    }
+
+    #[test]
+    fn test_size() {
+        let tmpdir = setup().mash("path_size");
+        let dir1 = tmpdir.mash("dir1");
+        let file1 = tmpdir.mash("file1");
+        let file2 = dir1.mash("file2");
+        let link1 = tmpdir.mash("link1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::write(&file1, "hello world\n").is_ok());
+        assert!(sys::write(&file2, "foo").is_ok());
+        assert!(sys::symlink(&link1, &file2).is_ok());
+
+        // files and recursive directories
+        assert_eq!(sys::size(&file1).unwrap(), 12);
+        assert_eq!(file2.size().unwrap(), 3);
+        assert_eq!(dir1.size().unwrap(), 3);
+        assert_eq!(sys::size(&tmpdir).unwrap(), 15);
+
+        // doesn't exist
+        assert!(sys::size(tmpdir.mash("foo")).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
+    #[test]
+    fn test_tree() {
+        let tmpdir = setup().mash("path_tree");
+        let dir1 = tmpdir.mash("dir1");
+        let dir2 = tmpdir.mash("dir2");
+        let dir3 = dir1.mash("dir3");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir3).is_ok());
+        assert!(sys::mkdir(&dir2).is_ok());
+        assert!(sys::touch(tmpdir.mash("file1")).is_ok());
+        assert!(sys::touch(dir1.mash("file2")).is_ok());
+        assert!(sys::touch(dir3.mash("file3")).is_ok());
+        assert!(sys::symlink(tmpdir.mash("a_link"), "file1").is_ok());
+
+        // full tree, directories first then files
+        let expected = "\
+tests/temp/path_tree
+├── dir1
+│   ├── dir3
+│   │   └── file3
+│   └── file2
+├── dir2
+├── a_link -> file1
+└── file1
+";
+        assert_eq!(sys::tree("tests/temp/path_tree").unwrap(), expected);
+
+        // limited depth
+        let expected = "\
+tests/temp/path_tree
+├── dir1
+│   ├── dir3
+│   └── file2
+├── dir2
+├── a_link -> file1
+└── file1
+";
+        assert_eq!(sys::tree_p("tests/temp/path_tree").max_depth(2).render().unwrap(), expected);
+
+        // not a directory
+        assert!(sys::tree(tmpdir.mash("file1")).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_uid() {
         assert!(sys::uid(".").is_ok());
@@ -1804,6 +2791,38 @@ mod tests {
         assert_eq!(sys::is_dir("/foobar"), false);
     }
 
+    #[test]
+    fn test_is_empty() {
+        let tmpdir = setup().mash("path_is_empty");
+        let dir1 = tmpdir.mash("dir1");
+        let file1 = tmpdir.mash("file1");
+        let file2 = dir1.mash("file2");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::touch(&file1).is_ok());
+
+        // empty file and dir
+        assert!(sys::is_empty(&file1));
+        assert!(PathExt::is_empty(file1.as_path()));
+        assert!(sys::is_empty(&dir1));
+        assert!(PathExt::is_empty(dir1.as_path()));
+
+        // non-empty file and dir
+        assert!(sys::write(&file2, "foo").is_ok());
+        assert!(!sys::is_empty(&file2));
+        assert!(!PathExt::is_empty(file2.as_path()));
+        assert!(!sys::is_empty(&dir1));
+        assert!(!PathExt::is_empty(dir1.as_path()));
+
+        // doesn't exist
+        assert!(!sys::is_empty(tmpdir.mash("foo")));
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_is_exec() {
         let tmpdir = setup().mash("path_is_exec");
@@ -1841,6 +2860,31 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_is_readable_writable() {
+        let tmpdir = setup().mash("path_is_readable_writable");
+        let file1 = tmpdir.mash("file1");
+        let file2 = tmpdir.mash("file2");
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+
+        // doesn't exist
+        assert!(!sys::is_readable(&file1));
+        assert!(!sys::is_writable(&file1));
+
+        // writable temp file
+        assert!(sys::touch_p(&file1, 0o644).is_ok());
+        assert!(file1.is_readable());
+        assert!(file1.is_writable());
+
+        // readonly file, root bypasses the mode bits
+        assert!(sys::touch_p(&file2, 0o444).is_ok());
+        assert!(sys::is_readable(&file2));
+        assert_eq!(file2.is_writable(), user::is_root());
+
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_is_readonly() {
         let tmpdir = setup().mash("path_is_readonly");
@@ -1928,6 +2972,20 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_expand_braces() {
+        assert_eq!(expand_braces("foo"), vec!["foo"]);
+        assert_eq!(expand_braces("foo.{rs,toml}"), vec!["foo.rs", "foo.toml"]);
+        assert_eq!(expand_braces("{a,b}{c,d}"), vec!["ac", "ad", "bc", "bd"]);
+        assert_eq!(expand_braces("a{b,c{d,e}}f"), vec!["abf", "acdf", "acef"]);
+        assert_eq!(expand_braces("a{b,}"), vec!["ab", "a"]);
+        assert_eq!(expand_braces("a{}"), vec!["a{}"]);
+        assert_eq!(expand_braces("a{}{b,c}"), vec!["a{}b", "a{}c"]);
+        assert_eq!(expand_braces("a{b}"), vec!["a{b}"]);
+        assert_eq!(expand_braces("a{b,c"), vec!["a{b,c"]);
+        assert_eq!(expand_braces("a{{b,c}"), vec!["a{b", "a{c"]);
+    }
+
     #[test]
     fn test_glob() {
         let tmpdir = setup().mash("path_glob");
@@ -1959,12 +3017,80 @@ mod tests {
         assert_eq!(tmpdir.exists(), false);
     }
 
+    #[test]
+    fn test_glob_braces() {
+        let tmpdir = setup().mash("path_glob_braces");
+        let file1 = tmpdir.mash("foo.rs");
+        let file2 = tmpdir.mash("foo.toml");
+        let file3 = tmpdir.mash("foo.txt");
+        let file4 = tmpdir.mash("bar.rs");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        for x in [&file1, &file2, &file3, &file4].iter() {
+            assert!(sys::touch(x).is_ok());
+        }
+
+        // simple and globbed alternatives
+        assert_iter_eq(sys::glob(tmpdir.mash("foo.{rs,toml}")).unwrap(), vec![file1.clone(), file2.clone()]);
+        assert_iter_eq(sys::glob(tmpdir.mash("*.{toml,rs}")).unwrap(), vec![file2.clone(), file4.clone(), file1.clone()]);
+
+        // nested braces and duplicate matches
+        assert_iter_eq(sys::glob(tmpdir.mash("foo.{rs,t{oml,xt}}")).unwrap(), vec![file1.clone(), file2.clone(), file3.clone()]);
+        assert_iter_eq(sys::glob(tmpdir.mash("{foo,*}.rs")).unwrap(), vec![file1.clone(), file4]);
+
+        // empty, single item and unbalanced braces are literal
+        assert!(sys::glob(tmpdir.mash("foo.{}")).unwrap().is_empty());
+        assert!(sys::glob(tmpdir.mash("foo.{rs}")).unwrap().is_empty());
+        assert!(sys::glob(tmpdir.mash("foo.{rs,toml")).unwrap().is_empty());
+
+        // consumers of glob benefit
+        assert!(sys::chmod_p(tmpdir.mash("foo.{rs,toml}")).unwrap().mode(0o600).chmod().is_ok());
+        assert_eq!(file1.mode().unwrap(), 0o100600);
+        assert_eq!(file2.mode().unwrap(), 0o100600);
+        assert_ne!(file3.mode().unwrap(), 0o100600);
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_metadata() {
         let meta = sys::metadata(setup()).unwrap();
         assert_eq!(meta.is_dir(), true);
     }
 
+    #[test]
+    fn test_times() {
+        let tmpdir = setup().mash("path_times");
+        let file1 = tmpdir.mash("file1");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&tmpdir).is_ok());
+        let before = SystemTime::now() - Duration::from_secs(2);
+        assert!(sys::touch(&file1).is_ok());
+
+        // free functions and trait methods agree
+        assert!(sys::modified(&file1).unwrap() >= before);
+        assert!(sys::accessed(&file1).unwrap() >= before);
+        assert_eq!(sys::modified(&file1).unwrap(), file1.modified().unwrap());
+        assert_eq!(sys::accessed(&file1).unwrap(), file1.accessed().unwrap());
+        if let Ok(created) = sys::created(&file1) {
+            assert!(created >= before);
+            assert_eq!(created, file1.created().unwrap());
+        }
+
+        // doesn't exist
+        assert!(sys::modified(tmpdir.mash("foo")).is_err());
+        assert!(tmpdir.mash("foo").accessed().is_err());
+        assert!(sys::created(tmpdir.mash("foo")).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_paths() {
         let tmpdir = setup().mash("path_paths");
@@ -2016,6 +3142,34 @@ mod tests {
         assert_iter_eq(sys::parse_paths(":/foo1:/foo2/bar").unwrap(), paths);
     }
 
+    #[test]
+    fn test_read_dir_filtered() {
+        let tmpdir = setup().mash("path_read_dir_filtered");
+        let dir1 = tmpdir.mash("dir1.rs");
+        let file1 = tmpdir.mash("file1.rs");
+        let file2 = tmpdir.mash("file2.toml");
+        let file3 = tmpdir.mash("file3.rs");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::touch(&file3).is_ok());
+        assert!(sys::touch(&file1).is_ok());
+        assert!(sys::touch(&file2).is_ok());
+
+        // filter by extension
+        assert_iter_eq(sys::read_dir_filtered(&tmpdir, |x| x.ext().unwrap_or_default() == "rs").unwrap(), vec![dir1.clone(), file1.clone(), file3.clone()]);
+        assert_iter_eq(sys::read_dir_filtered(&tmpdir, |x| x.is_file() && x.has_suffix(".rs")).unwrap(), vec![file1, file3]);
+        assert!(sys::read_dir_filtered(&tmpdir, |_| false).unwrap().is_empty());
+
+        // not a dir or doesn't exist
+        assert!(sys::read_dir_filtered(&file2, |_| true).is_err());
+        assert!(sys::read_dir_filtered(tmpdir.mash("foo"), |_| true).is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_readlink() {
         let tmpdir = setup().mash("path_readlink");
@@ -2216,6 +3370,31 @@ mod tests {
         assert_eq!(path.has("bob"), false);
     }
 
+    #[test]
+    fn test_pathext_has_all() {
+        let path = PathBuf::from("/usr/local/bin");
+        assert!(path.has_all(vec!["usr", "local", "bin"]));
+        assert!(path.has_all(vec!["/usr/local", "bin"]));
+        assert!(path.has_all(vec![Path::new("local")]));
+        assert!(!path.has_all(vec!["usr", "lib"]));
+        assert!(!path.has_all(vec!["sbin"]));
+
+        // vacuously true for no needles
+        assert!(path.has_all(Vec::<&str>::new()));
+    }
+
+    #[test]
+    fn test_pathext_has_any() {
+        let path = PathBuf::from("/usr/local/bin");
+        assert!(path.has_any(vec!["lib", "bin"]));
+        assert!(path.has_any(vec!["/usr/local"]));
+        assert!(path.has_any(vec![PathBuf::from("local"), PathBuf::from("share")]));
+        assert!(!path.has_any(vec!["lib", "share", "sbin"]));
+
+        // false for no needles
+        assert!(!path.has_any(Vec::<&str>::new()));
+    }
+
     #[test]
     fn test_pathext_has_prefix() {
         let path = PathBuf::from("/foo/bar");
@@ -2230,6 +3409,18 @@ mod tests {
         assert_eq!(path.has_suffix("/bar"), true);
     }
 
+    #[test]
+    fn test_pathext_is_abs_clean() {
+        assert!(Path::new("/").is_abs_clean());
+        assert!(Path::new("/foo/bar").is_abs_clean());
+        assert!(!Path::new("/foo/./bar").is_abs_clean());
+        assert!(!Path::new("/foo/../bar").is_abs_clean());
+        assert!(!Path::new("/foo//bar").is_abs_clean());
+        assert!(!Path::new("/foo/bar/").is_abs_clean());
+        assert!(!Path::new("foo/bar").is_abs_clean());
+        assert!(!Path::new("").is_abs_clean());
+    }
+
     #[test]
     fn test_pathext_is_dir() {
         let tmpdir = setup().mash("path_pathext_is_dir");
@@ -2345,6 +3536,52 @@ mod tests {
         assert!(sys::remove_all(&tmpdir).is_ok());
     }
 
+    #[test]
+    fn test_pathext_resolve() {
+        let tmpdir = setup().mash("path_pathext_resolve");
+        let dir1 = tmpdir.mash("dir1");
+        let file1 = dir1.mash("file1");
+        let link1 = tmpdir.mash("link1");
+        let link2 = dir1.mash("link2");
+        let link3 = tmpdir.mash("link3");
+
+        // setup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+        assert!(sys::mkdir(&dir1).is_ok());
+        assert!(sys::touch(&file1).is_ok());
+
+        // non links resolve to themselves
+        assert_eq!(file1.resolve().unwrap(), file1);
+
+        // multi hop chain with relative and absolute targets
+        assert!(sys::symlink(&link2, "file1").is_ok());
+        assert!(sys::symlink(&link1, "dir1/link2").is_ok());
+        assert!(sys::symlink(&link3, &link1).is_ok());
+        assert_eq!(link2.resolve().unwrap(), file1);
+        assert_eq!(link1.resolve().unwrap(), file1);
+        assert_eq!(link3.resolve().unwrap(), file1);
+
+        // loops are detected
+        let loop1 = tmpdir.mash("loop1");
+        let loop2 = tmpdir.mash("loop2");
+        assert!(sys::symlink(&loop1, "loop2").is_ok());
+        assert!(sys::symlink(&loop2, "loop1").is_ok());
+        assert_eq!(loop1.resolve().unwrap_err().downcast_ref::<PathError>(), Some(&PathError::symlink_loop(&loop1)));
+
+        // chains deeper than 40 are rejected
+        let mut prev = file1.clone();
+        for i in 0..41 {
+            let link = tmpdir.mash(format!("deep{}", i));
+            assert!(sys::symlink(&link, &prev).is_ok());
+            prev = link;
+        }
+        assert_eq!(tmpdir.mash("deep39").resolve().unwrap(), file1);
+        assert!(tmpdir.mash("deep40").resolve().is_err());
+
+        // cleanup
+        assert!(sys::remove_all(&tmpdir).is_ok());
+    }
+
     #[test]
     fn test_pathext_setperms() {
         let tmpdir = setup().mash("path_pathbuf_setperms");
@@ -2456,6 +3693,25 @@ mod tests {
         assert_eq!(PathBuf::from("/foo"), PathBuf::from("/foo").trim_suffix("/"));
     }
 
+    #[test]
+    fn test_pathext_with_ext() {
+        // replace
+        assert_eq!(PathBuf::from("foo.md").with_ext("html").unwrap(), PathBuf::from("foo.html"));
+        assert_eq!(PathBuf::from("/foo/bar.md").with_ext(".html").unwrap(), PathBuf::from("/foo/bar.html"));
+        assert_eq!(PathBuf::from("foo.tar.gz").with_ext("tgz").unwrap(), PathBuf::from("foo.tar.tgz"));
+
+        // add
+        assert_eq!(PathBuf::from("foo").with_ext("html").unwrap(), PathBuf::from("foo.html"));
+        assert_eq!(PathBuf::from("/foo/bar").with_ext(".html").unwrap(), PathBuf::from("/foo/bar.html"));
+
+        // dot files have no extension
+        assert_eq!(PathBuf::from(".bashrc").with_ext("bak").unwrap(), PathBuf::from(".bashrc.bak"));
+        assert_eq!(PathBuf::from("/foo/.bashrc.bak").with_ext("orig").unwrap(), PathBuf::from("/foo/.bashrc.orig"));
+
+        // empty extension trims
+        assert_eq!(PathBuf::from("foo.md").with_ext("").unwrap(), PathBuf::from("foo"));
+    }
+
     #[test]
     fn test_pathcolorext() {
         assert_eq!("foo".black(), PathBuf::from("foo").black());
@@ -2,7 +2,17 @@ use crate::{
     errors::*,
     sys::{self, PathExt},
 };
-use std::{io, iter, mem, path::PathBuf, ptr};
+use lazy_static::lazy_static;
+use std::{ffi::CString, io, iter, mem, path::PathBuf, ptr, sync::RwLock};
+
+// Initial and maximum buffer sizes for reentrant passwd and group lookups
+const PASSWD_BUF_SIZE: usize = 2048;
+const PASSWD_BUF_MAX: usize = 1024 * 1024;
+
+lazy_static! {
+    // Per process cache of the current user
+    static ref CURRENT: RwLock<Option<User>> = RwLock::new(None);
+}
 
 // Implementation in Rust for the XDB Base Directory Specification
 // https://wiki.archlinux.org/index.php/XDG_Base_Directory
@@ -177,8 +187,16 @@ pub fn path_dirs() -> FuResult<Vec<PathBuf>> {
 // User functions
 // -------------------------------------------------------------------------------------------------
 
+/// Group provides information about a specific group.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Group {
+    pub gid: u32,             // group id
+    pub name: String,         // group name
+    pub members: Vec<String>, // names of the group's supplementary members
+}
+
 /// User provides options for a specific user.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
 pub struct User {
     pub uid: u32,           // user id
     pub gid: u32,           // user group id
@@ -219,6 +237,26 @@ pub fn current() -> FuResult<User> {
     Ok(user)
 }
 
+/// Get the current user from a per process cache, only performing the passwd lookup on first use
+/// or after the cache has been invalidated with `refresh`. Useful in hot loops where `current`
+/// would repeat the lookup each call. The cache is not updated automatically after switching
+/// users so call `refresh` after e.g. `setuid` or `switchuser`.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(user::current_cached().unwrap(), user::current().unwrap());
+/// ```
+pub fn current_cached() -> FuResult<User> {
+    if let Some(user) = CURRENT.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return Ok(user.clone());
+    }
+    let user = current()?;
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = Some(user.clone());
+    Ok(user)
+}
+
 /// Switches back to the original user under the sudo mask with no way to go back.
 ///
 /// ### Examples
@@ -306,6 +344,55 @@ pub fn getrids(uid: u32, gid: u32) -> (u32, u32) {
     }
 }
 
+/// Lookup a group by group name
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(user::group("root").unwrap().gid, 0);
+/// ```
+pub fn group<T: AsRef<str>>(name: T) -> FuResult<Group> {
+    let cname = CString::new(name.as_ref())?;
+    let f = |group, buf, len, res| unsafe { libc::getgrnam_r(cname.as_ptr(), group, buf, len, res) };
+    let group = getent(PASSWD_BUF_SIZE, f, to_group)?;
+    group.ok_or_else(|| UserError::group_does_not_exist(name).into())
+}
+
+/// Lookup a group by group id
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(user::group_gid(0).unwrap().name, "root");
+/// ```
+pub fn group_gid(gid: u32) -> FuResult<Group> {
+    let f = |group, buf, len, res| unsafe { libc::getgrgid_r(gid, group, buf, len, res) };
+    let group = getent(PASSWD_BUF_SIZE, f, to_group)?;
+    group.ok_or_else(|| UserError::group_does_not_exist(gid.to_string()).into())
+}
+
+// Private helper to convert a libc::group object into a Group object
+fn to_group(group: &libc::group) -> FuResult<Group> {
+    let name = unsafe { sys::libc::to_string(group.gr_name)? };
+
+    // Members are a null terminated array of user names
+    let mut members = vec![];
+    if !group.gr_mem.is_null() {
+        let mut i = 0;
+        loop {
+            let member = unsafe { *group.gr_mem.add(i) };
+            if member.is_null() {
+                break;
+            }
+            members.push(unsafe { sys::libc::to_string(member)? });
+            i += 1;
+        }
+    }
+    Ok(Group { gid: group.gr_gid, name, members })
+}
+
 /// Return true if the current user is the root user.
 ///
 /// ### Examples
@@ -327,19 +414,63 @@ pub fn is_root() -> bool {
 /// assert!(user::lookup(user::getuid()).is_ok());
 /// ```
 pub fn lookup(uid: u32) -> FuResult<User> {
-    // Get the libc::passwd by user id
-    let mut buf = vec![0; 2048];
-    let mut res = ptr::null_mut::<libc::passwd>();
-    let mut passwd = unsafe { mem::zeroed::<libc::passwd>() };
-    unsafe {
-        libc::getpwuid_r(uid, &mut passwd, buf.as_mut_ptr(), buf.len(), &mut res);
-    }
-    if res.is_null() || res != &mut passwd {
-        return Err(UserError::does_not_exist_by_id(uid).into());
+    lookup_uid(uid)
+}
+
+/// Lookup a user by user name
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(user::lookup_by_name("root").unwrap().uid, 0);
+/// ```
+pub fn lookup_by_name<T: AsRef<str>>(name: T) -> FuResult<User> {
+    let cname = CString::new(name.as_ref())?;
+    let f = |passwd, buf, len, res| unsafe { libc::getpwnam_r(cname.as_ptr(), passwd, buf, len, res) };
+    let user = getent(PASSWD_BUF_SIZE, f, to_user)?;
+    user.ok_or_else(|| UserError::does_not_exist_by_name(name).into())
+}
+
+/// Lookup a user by user id. The passwd buffer is grown as needed to fit large entries.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(user::lookup_uid(0).unwrap().name, "root");
+/// ```
+pub fn lookup_uid(uid: u32) -> FuResult<User> {
+    let f = |passwd, buf, len, res| unsafe { libc::getpwuid_r(uid, passwd, buf, len, res) };
+    let user = getent(PASSWD_BUF_SIZE, f, to_user)?;
+    user.ok_or_else(|| UserError::does_not_exist_by_id(uid).into())
+}
+
+// Private helper to call the given reentrant passwd or group function `f` starting with a buffer
+// of `size` bytes and doubling it on ERANGE. The entry is converted with `convert` while the
+// buffer is still alive. Returns `None` if there is no matching entry.
+fn getent<T, U, F, C>(size: usize, mut f: F, convert: C) -> FuResult<Option<U>>
+where
+    F: FnMut(*mut T, *mut libc::c_char, usize, *mut *mut T) -> libc::c_int,
+    C: Fn(&T) -> FuResult<U>,
+{
+    let mut size = size.max(1);
+    loop {
+        let mut buf = vec![0; size];
+        let mut res = ptr::null_mut::<T>();
+        let mut entry = unsafe { mem::zeroed::<T>() };
+        match f(&mut entry, buf.as_mut_ptr(), buf.len(), &mut res) {
+            libc::ERANGE if size < PASSWD_BUF_MAX => size *= 2,
+            0 if res.is_null() => return Ok(None),
+            0 => return Ok(Some(convert(&entry)?)),
+            errno => return Err(io::Error::from_raw_os_error(errno).into()),
+        }
     }
+}
 
-    // Convert libc::passwd object into a User object
-    //----------------------------------------------------------------------------------------------
+// Private helper to convert a libc::passwd object into a User object
+fn to_user(passwd: &libc::passwd) -> FuResult<User> {
+    let uid = passwd.pw_uid;
     let gid = passwd.pw_gid;
 
     // User name for the lookedup user. We always want this and it should always exist.
@@ -417,6 +548,35 @@ pub fn pause_sudo() -> FuResult<()> {
     }
 }
 
+/// Invalidate the per process cache used by `current_cached` so the next call performs a fresh
+/// lookup e.g. after switching users with `setuid`.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// user::refresh();
+/// assert_eq!(user::current_cached().unwrap(), user::current().unwrap());
+/// ```
+pub fn refresh() {
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Return an error indicating that root privileges are required if the current user isn't root.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(user::require_root().is_ok(), user::is_root());
+/// ```
+pub fn require_root() -> FuResult<()> {
+    match is_root() {
+        true => Ok(()),
+        false => Err(UserError::requires_root().into()),
+    }
+}
+
 /// Set the user ID for the current user.
 ///
 /// ### Examples
@@ -518,6 +678,69 @@ pub fn switchuser(ruid: u32, euid: u32, suid: u32, rgid: u32, egid: u32, sgid: u
 mod tests {
     use crate::prelude::*;
 
+    #[test]
+    fn test_user_current_cached() {
+        // repeated calls return the same data as a direct lookup
+        let user = user::current().unwrap();
+        assert_eq!(user::current_cached().unwrap(), user);
+        assert_eq!(user::current_cached().unwrap(), user);
+
+        // simulate an id change by seeding the cache then refresh to pick up the real user
+        let other = user::User { uid: user.uid + 1, name: "other".to_string(), ..user.clone() };
+        *super::CURRENT.write().unwrap() = Some(other.clone());
+        assert_eq!(user::current_cached().unwrap(), other);
+        user::refresh();
+        assert_eq!(user::current_cached().unwrap(), user);
+    }
+
+    #[test]
+    fn test_user_lookup_by_name() {
+        let root = user::lookup_by_name("root").unwrap();
+        assert_eq!(root.uid, 0);
+        assert_eq!(root.gid, 0);
+        assert_eq!(root.name, "root");
+        assert_eq!(root, user::lookup(0).unwrap());
+        let err = user::lookup_by_name("fungus_no_such_user").unwrap_err();
+        assert_eq!(err.downcast_ref::<UserError>(), Some(&UserError::does_not_exist_by_name("fungus_no_such_user")));
+        assert!(user::lookup_by_name("ro\0ot").is_err());
+    }
+
+    #[test]
+    fn test_user_lookup_uid() {
+        let root = user::lookup_uid(0).unwrap();
+        assert_eq!(root.name, "root");
+        assert_eq!(user::lookup_by_name(&root.name).unwrap().uid, 0);
+        assert_eq!(user::lookup(0).unwrap(), root);
+        let err = user::lookup_uid(u32::MAX - 1).unwrap_err();
+        assert_eq!(err.downcast_ref::<UserError>(), Some(&UserError::does_not_exist_by_id(u32::MAX - 1)));
+
+        // a tiny buffer is grown on ERANGE
+        let mut sizes = vec![];
+        let f = |passwd, buf, len, res| {
+            sizes.push(len);
+            unsafe { libc::getpwuid_r(0, passwd, buf, len, res) }
+        };
+        let user = super::getent(1, f, super::to_user);
+        assert_eq!(user.unwrap(), Some(root));
+        assert!(sizes.len() > 1);
+        assert_eq!(sizes[..2], [1, 2]);
+    }
+
+    #[test]
+    fn test_user_group() {
+        let root = user::group_gid(0).unwrap();
+        assert_eq!(root.gid, 0);
+        assert_eq!(root.name, "root");
+        assert_eq!(user::group(&root.name).unwrap(), root);
+        assert_eq!(user::group_gid(user::getgid()).unwrap().gid, user::getgid());
+
+        // missing groups
+        let err = user::group("fungus_no_such_group").unwrap_err();
+        assert_eq!(err.downcast_ref::<UserError>(), Some(&UserError::group_does_not_exist("fungus_no_such_group")));
+        let err = user::group_gid(u32::MAX - 1).unwrap_err();
+        assert_eq!(err.downcast_ref::<UserError>(), Some(&UserError::group_does_not_exist((u32::MAX - 1).to_string())));
+    }
+
     #[test]
     fn test_user_home() {
         let home_str = sys::var("HOME").unwrap();
@@ -547,6 +770,17 @@ mod tests {
         // assert!(user::setuid(user::getuid()).is_ok());
     }
 
+    #[test]
+    fn test_user_require_root() {
+        match user::is_root() {
+            true => assert!(user::require_root().is_ok()),
+            false => {
+                let err = user::require_root().unwrap_err();
+                assert_eq!(err.downcast_ref::<UserError>(), Some(&UserError::RequiresRoot));
+            },
+        }
+    }
+
     #[test]
     fn test_user_dirs() {
         assert!(user::home_dir().is_ok());
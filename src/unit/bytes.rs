@@ -24,6 +24,47 @@ pub fn to_human(val: u64) -> String {
     format!("{} {}", result.trim_suffix(".00"), unit)
 }
 
+/// Converts the given value in bytes to a short human readable format using binary (1024) units
+/// with one decimal place e.g. 1536 = 1.5K. Values under 1K are given as whole numbers.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(unit::format_bytes(1023), "1023");
+/// assert_eq!(unit::format_bytes(1536), "1.5K");
+/// ```
+pub fn format_bytes(val: u64) -> String {
+    format_bytes_p(val, false)
+}
+
+/// Wraps `format_bytes` allowing for the use of decimal SI (1000) units instead of binary units.
+///
+/// ### Examples
+/// ```
+/// use fungus::prelude::*;
+///
+/// assert_eq!(unit::format_bytes_p(1500, true), "1.5K");
+/// assert_eq!(unit::format_bytes_p(1500, false), "1.5K");
+/// assert_eq!(unit::format_bytes_p(2000, true), "2.0K");
+/// ```
+pub fn format_bytes_p(val: u64, decimal: bool) -> String {
+    let base = if decimal { 1000.0 } else { 1024.0 };
+    let units = ["K", "M", "G", "T", "P", "E"];
+    if (val as f64) < base {
+        return val.to_string();
+    }
+
+    // Scale down keeping rounding from pushing the value up to the base
+    let mut value = val as f64 / base;
+    let mut i = 0;
+    while (value * 10.0).round() / 10.0 >= base && i < units.len() - 1 {
+        value /= base;
+        i += 1;
+    }
+    format!("{:.1}{}", value, units[i])
+}
+
 /// Convert the given value in bytes to increments of KiB
 pub fn to_kib(value: u64) -> f64 {
     value as f64 / KIBIBYTE as f64
@@ -50,6 +91,30 @@ pub fn to_tib(value: u64) -> f64 {
 mod tests {
     use crate::prelude::*;
 
+    #[test]
+    fn test_format_bytes() {
+        // binary units
+        assert_eq!(unit::format_bytes(0), "0");
+        assert_eq!(unit::format_bytes(1023), "1023");
+        assert_eq!(unit::format_bytes(1024), "1.0K");
+        assert_eq!(unit::format_bytes(1536), "1.5K");
+        assert_eq!(unit::format_bytes(unit::MEBIBYTE - 1), "1.0M");
+        assert_eq!(unit::format_bytes(unit::MEBIBYTE), "1.0M");
+        assert_eq!(unit::format_bytes(2 * unit::MEBIBYTE + 300 * unit::KIBIBYTE), "2.3M");
+        assert_eq!(unit::format_bytes(4 * unit::GIBIBYTE), "4.0G");
+        assert_eq!(unit::format_bytes(3 * unit::TEBIBYTE), "3.0T");
+        assert_eq!(unit::format_bytes(u64::MAX), "16.0E");
+
+        // decimal units
+        assert_eq!(unit::format_bytes_p(999, true), "999");
+        assert_eq!(unit::format_bytes_p(1000, true), "1.0K");
+        assert_eq!(unit::format_bytes_p(1023, true), "1.0K");
+        assert_eq!(unit::format_bytes_p(1536, true), "1.5K");
+        assert_eq!(unit::format_bytes_p(999_999, true), "1.0M");
+        assert_eq!(unit::format_bytes_p(2_300_000, true), "2.3M");
+        assert_eq!(unit::format_bytes_p(4_000_000_000, true), "4.0G");
+    }
+
     #[test]
     fn test_to_human() {
         assert_eq!(unit::bytes::to_human(10), "10 bytes");
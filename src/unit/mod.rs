@@ -1,4 +1,4 @@
 pub mod bytes;
 pub mod time;
 
-pub use bytes::{GIBIBYTE, KIBIBYTE, MEBIBYTE, TEBIBYTE};
+pub use bytes::{format_bytes, format_bytes_p, GIBIBYTE, KIBIBYTE, MEBIBYTE, TEBIBYTE};
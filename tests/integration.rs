@@ -1,7 +1,106 @@
 use fungus::prelude::*;
+use std::{env, ffi::OsStr, os::unix::ffi::OsStrExt, sync::Mutex};
+
+// Serializes the tests below that change process wide state so they don't observe each other
+static PROCESS_LOCK: Mutex<()> = Mutex::new(());
 
 #[test]
 fn test_use_syntax() {
     let home = user::home_dir().unwrap();
     assert_eq!(PathBuf::from(&home), sys::abs("~").unwrap());
 }
+
+// Changing the working directory affects the whole process so this lives here rather than with
+// the unit tests which depend on the working directory.
+#[test]
+fn test_cwd_guard() {
+    let _lock = PROCESS_LOCK.lock().unwrap_or_else(|x| x.into_inner());
+    let cwd = sys::cwd().unwrap();
+    let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("integration_cwd_guard");
+
+    // setup
+    assert!(sys::remove_all(&tmpdir).is_ok());
+    assert!(sys::mkdir(&tmpdir).is_ok());
+
+    // invalid target leaves the working directory alone
+    assert!(sys::cwd_guard(tmpdir.mash("foo")).is_err());
+    assert_eq!(sys::cwd().unwrap(), cwd);
+
+    // restored on drop
+    {
+        let _guard = sys::cwd_guard(&tmpdir).unwrap();
+        assert_eq!(sys::cwd().unwrap(), tmpdir);
+    }
+    assert_eq!(sys::cwd().unwrap(), cwd);
+
+    // cleanup
+    assert!(sys::remove_all(&tmpdir).is_ok());
+}
+
+// Restoring the environment affects the whole process so this lives here rather than with the unit
+// tests which set their own variables in parallel.
+#[test]
+fn test_env_snapshot_restore() {
+    let _lock = PROCESS_LOCK.lock().unwrap_or_else(|x| x.into_inner());
+    sys::set_var("ENV_SNAPSHOT_KEEP", "foo");
+    sys::set_var("ENV_SNAPSHOT_REMOVE", "foo");
+    sys::unset_var("ENV_SNAPSHOT_ADD");
+    let snap = sys::env_snapshot();
+    assert_eq!(snap.get("ENV_SNAPSHOT_KEEP"), Some(&"foo".to_string()));
+
+    // mutate the environment
+    sys::set_var("ENV_SNAPSHOT_KEEP", "bar");
+    sys::unset_var("ENV_SNAPSHOT_REMOVE");
+    sys::set_var("ENV_SNAPSHOT_ADD", "foo");
+    assert_ne!(sys::env_snapshot(), snap);
+
+    // restore
+    sys::env_restore(&snap);
+    assert_eq!(sys::env_snapshot(), snap);
+    assert_eq!(sys::var("ENV_SNAPSHOT_KEEP"), Ok("foo".to_string()));
+    assert_eq!(sys::var("ENV_SNAPSHOT_REMOVE"), Ok("foo".to_string()));
+    assert!(sys::var("ENV_SNAPSHOT_ADD").is_err());
+
+    // non unicode variables are skipped and left alone
+    let invalid = OsStr::from_bytes(b"foo\xff");
+    sys::set_var("ENV_SNAPSHOT_INVALID", invalid);
+    let snap = sys::env_snapshot();
+    assert!(!snap.contains_key("ENV_SNAPSHOT_INVALID"));
+    sys::env_restore(&snap);
+    assert_eq!(env::var_os("ENV_SNAPSHOT_INVALID"), Some(invalid.to_os_string()));
+
+    // cleanup
+    sys::unset_var("ENV_SNAPSHOT_KEEP");
+    sys::unset_var("ENV_SNAPSHOT_REMOVE");
+    sys::unset_var("ENV_SNAPSHOT_INVALID");
+}
+
+// Changing PATH affects the whole process so this lives here rather than with the unit tests
+// which look up executables in parallel.
+#[test]
+fn test_lookup_all() {
+    let _lock = PROCESS_LOCK.lock().unwrap_or_else(|x| x.into_inner());
+    let tmpdir = PathBuf::from("tests/temp").abs().unwrap().mash("integration_lookup_all");
+    let dir1 = tmpdir.mash("dir1");
+    let dir2 = tmpdir.mash("dir2");
+    let file1 = dir1.mash("fungus_lookup_all");
+    let file2 = dir2.mash("fungus_lookup_all");
+
+    // setup
+    assert!(sys::remove_all(&tmpdir).is_ok());
+    assert!(sys::mkdir(&dir1).is_ok());
+    assert!(sys::mkdir(&dir2).is_ok());
+    assert!(sys::touch_p(&file1, 0o755).is_ok());
+    assert!(sys::touch_p(&file2, 0o755).is_ok());
+
+    // both dirs at the front of PATH are searched in order
+    let path = format!("{}:{}:{}", dir1.display(), dir2.display(), sys::var("PATH").unwrap_or_default());
+    {
+        let _guard = sys::var_guard("PATH", &path);
+        assert_eq!(exec::lookup_all("fungus_lookup_all").unwrap(), vec![file1, file2]);
+    }
+    assert!(exec::lookup_all("fungus_lookup_all").unwrap().is_empty());
+
+    // cleanup
+    assert!(sys::remove_all(&tmpdir).is_ok());
+}